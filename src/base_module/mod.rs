@@ -1,15 +1,20 @@
+use std::sync::Arc;
 use rand::Rng;
 use num::Integer;
-use block::{BlockId, Block, LightType};
+use block::{BlockId, BlockState, Block, BlockBehavior, BlockProperties, LightType, StateDefinition, StateProperty};
 use graphics::DrawType;
+use geometry::{Direction, BlockBox};
+use item::{Container, CONTAINER_TYPE, DroppedBlock, DROPPED_BLOCK_TYPE};
 use module::*;
 use world::*;
 use world::generator::structure::*;
 use world::generator::*;
 use world::generator::noise::NoiseParameters;
-use world::generator::overworld::GroundGen;
+use world::generator::overworld::{GroundGen, CaveGen, OreGen, Climate};
 use world::biome::*;
-use geometry::Direction;
+use self::vegetation::{TreeFinder, PlantFinder, BoulderFinder, surface_positions};
+
+mod vegetation;
 
 struct BaseModule {}
 
@@ -21,31 +26,90 @@ impl Init1 for InitT1 {
     fn run(self: Box<Self>, p1: &mut Phase1) -> Box<Init2> {
         p1.biomes.register(Biome::new("plain".into()));
         p1.biomes.register(Biome::new("rock".into()));
-        p1.blocks.add(Block::new(
-            DrawType::FullOpaqueBlock([p1.textures.get("stone"); 6]),
-            LightType::Opaque,
-            "stone".into(),
-        ));
-        p1.blocks.add(Block::new(
-            DrawType::FullOpaqueBlock([p1.textures.get("dirt"); 6]),
-            LightType::Opaque,
-            "dirt".into(),
-        ));
-        p1.blocks.add(Block::new(
-            {
-                let mut texture = [p1.textures.get("grass_side"); 6];
-                texture[Direction::PosY as usize] = p1.textures.get("grass");
-                texture[Direction::NegY as usize] = p1.textures.get("dirt");
-                DrawType::FullOpaqueBlock(texture)
-            },
-            LightType::Opaque,
-            "grass".into(),
-        ));
-        p1.blocks.add(Block::new(
-            DrawType::FullOpaqueBlock([p1.textures.get("debug"); 6]),
-            LightType::Source(15),
-            "debug_light".into(),
-        ));
+        p1.biomes.register(Biome::new("ocean".into()));
+        //the base module cannot work without its blocks
+        if let Err(errors) = p1.load_block_file("blocks/base.toml") {
+            let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            panic!("cannot load base blocks:\n{}", messages.join("\n"));
+        }
+        for plant in &["tall_grass", "flower"] {
+            if let Some(id) = p1.blocks.by_name(plant) {
+                p1.blocks.set_behavior(id, Box::new(NeedsGround));
+            }
+        }
+        if let (Some(grass), Some(dirt)) = (p1.blocks.by_name("grass"), p1.blocks.by_name("dirt")) {
+            p1.blocks.set_behavior(grass, Box::new(GrassSpread { grass, dirt }));
+        }
+        p1.block_controllers.register(CONTAINER_TYPE, Box::new(Container::deserialize));
+        p1.entities.register(DROPPED_BLOCK_TYPE, Box::new(DroppedBlock::deserialize));
+        if let Some(chest) = p1.blocks.by_name("chest") {
+            p1.blocks.set_behavior(chest, Box::new(Chest { slots: CHEST_SLOTS }));
+        }
+        let log_side = p1.textures.get("log_side");
+        let log_top = p1.textures.get("log_top");
+        p1.blocks.add(
+            Block::new(DrawType::None, LightType::Opaque, "log".into())
+                .with_properties(BlockProperties {
+                    break_ticks: Some(20),
+                    ..Default::default()
+                })
+                .with_states(
+                    StateDefinition::new(vec![StateProperty::new("axis", &["y", "x", "z"])]),
+                    |states, meta| {
+                        let mut texture = [log_side; 6];
+                        let ends = match states.value_name(meta, "axis") {
+                            Some("x") => [Direction::PosX, Direction::NegX],
+                            Some("z") => [Direction::PosZ, Direction::NegZ],
+                            _ => [Direction::PosY, Direction::NegY],
+                        };
+                        for &d in &ends {
+                            texture[d as usize] = log_top;
+                        }
+                        DrawType::FullOpaqueBlock(texture)
+                    },
+                ),
+        );
+        let stone = p1.textures.get("stone");
+        p1.blocks.add(
+            Block::new(DrawType::None, LightType::Transparent, "stone_slab".into())
+                .with_properties(BlockProperties {
+                    break_ticks: Some(15),
+                    ..Default::default()
+                })
+                .with_states(
+                    StateDefinition::new(vec![StateProperty::new("half", &["bottom", "top"])]),
+                    |states, meta| {
+                        let half = if states.value_name(meta, "half") == Some("top") {
+                            BlockBox::new([0., 0.5, 0.], [1., 1., 1.])
+                        } else {
+                            BlockBox::new([0., 0., 0.], [1., 0.5, 1.])
+                        };
+                        DrawType::Shape(vec![half], [stone; 6])
+                    },
+                ),
+        );
+        p1.blocks.add(
+            Block::new(DrawType::None, LightType::Transparent, "stone_stairs".into())
+                .with_properties(BlockProperties {
+                    break_ticks: Some(30),
+                    ..Default::default()
+                })
+                .with_states(
+                    StateDefinition::new(vec![
+                        StateProperty::new("facing", &["pos_x", "neg_x", "pos_z", "neg_z"]),
+                    ]),
+                    |states, meta| {
+                        //the upper step is on the side the stairs are facing
+                        let step = match states.value_name(meta, "facing") {
+                            Some("neg_x") => BlockBox::new([0., 0.5, 0.], [0.5, 1., 1.]),
+                            Some("pos_z") => BlockBox::new([0., 0.5, 0.5], [1., 1., 1.]),
+                            Some("neg_z") => BlockBox::new([0., 0.5, 0.], [1., 1., 0.5]),
+                            _ => BlockBox::new([0.5, 0.5, 0.], [1., 1., 1.]),
+                        };
+                        DrawType::Shape(vec![BlockBox::new([0., 0., 0.], [1., 0.5, 1.]), step], [stone; 6])
+                    },
+                ),
+        );
         Box::new(InitT2())
     }
 }
@@ -61,7 +125,10 @@ impl Init2 for InitT2 {
             0,
             GroundGen::new()
                 .push_layer(p2.blocks.by_name("grass").unwrap(), 1., 1.)
-                .push_layer(p2.blocks.by_name("dirt").unwrap(), 1., 4.),
+                .push_layer(p2.blocks.by_name("dirt").unwrap(), 1., 4.)
+                .beach_height(2)
+                .push_beach_layer(p2.blocks.by_name("sand").unwrap(), 2., 4.),
+            Climate::new((0.3, 1.), (0.3, 0.7)),
         );
         p2.add_overworld_biome(
             p2.biomes.by_name("rock").unwrap(),
@@ -73,7 +140,70 @@ impl Init2 for InitT2 {
             ),
             32,
             GroundGen::new(),
+            Climate::new((0., 0.3), (0., 1.)),
+        );
+        p2.add_overworld_biome(
+            p2.biomes.by_name("ocean").unwrap(),
+            NoiseParameters::new()
+                .push(6., 256., None, None)
+                .push(2., 32., None, None),
+            -24,
+            GroundGen::new()
+                .push_layer(p2.blocks.by_name("sand").unwrap(), 2., 5.),
+            Climate::new((0.3, 1.), (0.7, 1.)),
+        );
+        p2.set_sea_level(0, p2.blocks.by_name("water").unwrap());
+        p2.set_overworld_caves(
+            p2.biomes.by_name("plain").unwrap(),
+            CaveGen::new().tunnels(64., 0.06).caverns(48., 0.6).min_depth(4),
+        );
+        p2.set_overworld_caves(
+            p2.biomes.by_name("rock").unwrap(),
+            CaveGen::new().tunnels(64., 0.06).caverns(32., 0.5).min_depth(2),
+        );
+        let stone = p2.blocks.by_name("stone").unwrap();
+        p2.add_ore(
+            OreGen::new(p2.blocks.by_name("coal_ore").unwrap(), stone)
+                .vein_size(16)
+                .count(6)
+                .height(-128..64),
         );
+        p2.add_ore(
+            OreGen::new(p2.blocks.by_name("iron_ore").unwrap(), stone)
+                .vein_size(8)
+                .count(3)
+                .height(-256..0)
+                .biomes(vec![p2.biomes.by_name("rock").unwrap()]),
+        );
+        let plain = p2.biomes.by_name("plain").unwrap();
+        let rock = p2.biomes.by_name("rock").unwrap();
+        p2.add_structure(Box::new(TreeFinder {
+            log: p2.blocks.by_name("log").unwrap(),
+            leaves: p2.blocks.by_name("leaves").unwrap(),
+            biomes: vec![plain],
+            per_chunk: 3,
+        }));
+        p2.add_structure(Box::new(PlantFinder {
+            plants: vec![
+                p2.blocks.by_name("tall_grass").unwrap(),
+                p2.blocks.by_name("tall_grass").unwrap(),
+                p2.blocks.by_name("flower").unwrap(),
+            ],
+            biomes: vec![plain],
+            per_chunk: 24,
+        }));
+        p2.add_structure(Box::new(BoulderFinder {
+            block: stone,
+            biomes: vec![rock],
+            per_chunk: 1,
+        }));
+        let chest = p2.blocks.by_name("chest").unwrap();
+        let supplies = [(p2.blocks.by_name("log").unwrap(), 16), (p2.blocks.by_name("iron_ore").unwrap(), 4)];
+        p2.add_structure(Box::new(SupplyChestFinder {
+            chest,
+            contents: Container::data(&p2.blocks, CHEST_SLOTS, &supplies),
+            biomes: vec![plain, rock],
+        }));
         p2.add_structure(Box::new(CrossFinder {
             block: p2.blocks.by_name("debug_light").unwrap(),
         }));
@@ -90,6 +220,61 @@ pub fn module() -> Box<Module> {
     Box::new(BaseModule {})
 }
 
+/// a chest with some supplies found on the surface of about one in 64 chunks
+struct SupplyChestFinder {
+    chest: BlockId,
+    /// data of the container of the chest
+    contents: Vec<u8>,
+    biomes: Vec<BiomeId>,
+}
+
+impl StructureFinder for SupplyChestFinder {
+    fn push_structures(
+        &self,
+        chunk: ChunkPos,
+        rand: &WorldRngSeeder,
+        t: &TerrainInformation,
+        out: &mut StructureList,
+    ) {
+        let mut rand = rand.push_num(4).rng();
+        if !rand.gen_weighted_bool(64) {
+            return;
+        }
+        for pos in surface_positions(chunk, &mut rand, t, 1, &self.biomes) {
+            out.push(
+                Box::new(SupplyChest {
+                    chest: self.chest,
+                    contents: self.contents.clone(),
+                }),
+                pos,
+                self.max_bounds(),
+            );
+        }
+    }
+    fn max_bounds(&self) -> [[i32; 2]; 3] {
+        [[0, 0], [0, 1], [0, 0]]
+    }
+}
+
+struct SupplyChest {
+    chest: BlockId,
+    contents: Vec<u8>,
+}
+
+impl Structure for SupplyChest {
+    fn generate<'a>(
+        &self,
+        chunk: &'a mut GeneratingChunk<'a>,
+        _: &WorldRngSeeder,
+        _: &TerrainInformation,
+    ) {
+        if chunk.get_block([0, 1, 0]) == Some(BlockId::empty()) {
+            chunk.set_block([0, 1, 0], self.chest);
+            chunk.add_block_controller([0, 1, 0], CONTAINER_TYPE, self.contents.clone());
+        }
+    }
+}
+
 struct CrossFinder {
     block: BlockId,
 }
@@ -139,3 +324,66 @@ impl Structure for CrossGenerator {
         }
     }
 }
+
+/// plants break when the block below them stops being solid
+struct NeedsGround;
+
+impl BlockBehavior for NeedsGround {
+    fn on_neighbor_changed(&self, world: &World, pos: BlockPos, _: BlockState, direction: Direction) {
+        if direction != Direction::NegY {
+            return;
+        }
+        let supported = world
+            .get_block(pos.facing(Direction::NegY))
+            .map_or(true, |b| world.game_data().blocks().properties(b).collides);
+        if !supported {
+            world.set_block(pos, BlockId::empty()).ok();
+        }
+    }
+}
+
+/// grass turns into dirt when covered and spreads to uncovered dirt next to it
+struct GrassSpread {
+    grass: BlockId,
+    dirt: BlockId,
+}
+
+impl GrassSpread {
+    /// blocks below an unloaded chunk are not covered, so grass does not turn into dirt at chunk borders
+    fn covered(world: &World, pos: BlockPos) -> bool {
+        world
+            .get_block(pos.facing(Direction::PosY))
+            .map_or(false, |b| world.game_data().blocks().light_type(b).is_opaque())
+    }
+}
+
+impl BlockBehavior for GrassSpread {
+    fn on_random_tick(&self, world: &World, pos: BlockPos, _: BlockState, rng: &mut WorldGenRng) {
+        if Self::covered(world, pos) {
+            world.set_block(pos, self.dirt).ok();
+            return;
+        }
+        let target = BlockPos([
+            pos[0] + rng.gen_range(-1, 2),
+            pos[1] + rng.gen_range(-1, 2),
+            pos[2] + rng.gen_range(-1, 2),
+        ]);
+        if world.get_block(target) == Some(self.dirt) && !Self::covered(world, target) {
+            world.set_block(target, self.grass).ok();
+        }
+    }
+}
+
+const CHEST_SLOTS: usize = 30;
+
+/// chests get a container when they are placed
+struct Chest {
+    slots: usize,
+}
+
+impl BlockBehavior for Chest {
+    fn on_placed(&self, world: &World, pos: BlockPos, _: BlockState) {
+        let container = Container::new(Arc::clone(world.game_data()), self.slots);
+        world.create_block_controller(pos, Arc::new(container)).ok();
+    }
+}
@@ -0,0 +1,206 @@
+use rand::Rng;
+use num::Integer;
+use block::BlockId;
+use world::*;
+use world::biome::BiomeId;
+use world::generator::structure::*;
+use world::generator::TerrainInformation;
+
+/// picks up to count random surface positions in the chunk containing the surface
+/// positions outside the given biomes or below sea level are skipped
+pub fn surface_positions<R: Rng>(
+    chunk: ChunkPos,
+    rand: &mut R,
+    t: &TerrainInformation,
+    count: usize,
+    biomes: &[BiomeId],
+) -> Vec<BlockPos> {
+    let cs = CHUNK_SIZE as i32;
+    (0..count)
+        .filter_map(|_| {
+            let x = chunk[0] * cs + rand.gen_range(0, cs);
+            let z = chunk[2] * cs + rand.gen_range(0, cs);
+            let surface = t.surface_y(x, z);
+            if surface.div_floor(&cs) == chunk[1] && t.sea_level().map_or(true, |l| surface >= l) &&
+                biomes.contains(&t.biome_at(x, z))
+            {
+                Some(BlockPos([x, surface, z]))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub struct TreeFinder {
+    pub log: BlockId,
+    pub leaves: BlockId,
+    pub biomes: Vec<BiomeId>,
+    pub per_chunk: usize,
+}
+
+const MAX_TREE_HEIGHT: i32 = 7;
+
+impl StructureFinder for TreeFinder {
+    fn push_structures(
+        &self,
+        chunk: ChunkPos,
+        rand: &WorldRngSeeder,
+        t: &TerrainInformation,
+        out: &mut StructureList,
+    ) {
+        let mut rand = rand.push_num(1).rng();
+        for pos in surface_positions(chunk, &mut rand, t, self.per_chunk, &self.biomes) {
+            out.push(
+                Box::new(Tree {
+                    log: self.log,
+                    leaves: self.leaves,
+                    height: rand.gen_range(4, MAX_TREE_HEIGHT + 1),
+                }),
+                pos,
+                self.max_bounds(),
+            );
+        }
+    }
+    fn max_bounds(&self) -> [[i32; 2]; 3] {
+        [[2, 2], [0, MAX_TREE_HEIGHT + 1], [2, 2]]
+    }
+}
+
+struct Tree {
+    log: BlockId,
+    leaves: BlockId,
+    height: i32,
+}
+
+impl Structure for Tree {
+    fn generate<'a>(
+        &self,
+        chunk: &'a mut GeneratingChunk<'a>,
+        _: &WorldRngSeeder,
+        _: &TerrainInformation,
+    ) {
+        for y in 1..self.height + 1 {
+            chunk.set_block([0, y, 0], self.log);
+        }
+        for y in self.height - 2..self.height + 2 {
+            let radius: i32 = if y < self.height { 2 } else { 1 };
+            for x in -radius..radius + 1 {
+                for z in -radius..radius + 1 {
+                    if x.abs() == radius && z.abs() == radius {
+                        continue;
+                    }
+                    if chunk.get_block([x, y, z]) == Some(BlockId::empty()) {
+                        chunk.set_block([x, y, z], self.leaves);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// single blocks placed on top of the surface
+/// nothing is placed if plants is empty
+pub struct PlantFinder {
+    pub plants: Vec<BlockId>,
+    pub biomes: Vec<BiomeId>,
+    pub per_chunk: usize,
+}
+
+impl StructureFinder for PlantFinder {
+    fn push_structures(
+        &self,
+        chunk: ChunkPos,
+        rand: &WorldRngSeeder,
+        t: &TerrainInformation,
+        out: &mut StructureList,
+    ) {
+        let mut rand = rand.push_num(2).rng();
+        for pos in surface_positions(chunk, &mut rand, t, self.per_chunk, &self.biomes) {
+            let block = match rand.choose(&self.plants) {
+                Some(&block) => block,
+                None => return,
+            };
+            out.push(Box::new(Plant { block }), pos, self.max_bounds());
+        }
+    }
+    fn max_bounds(&self) -> [[i32; 2]; 3] {
+        [[0, 0], [0, 1], [0, 0]]
+    }
+}
+
+struct Plant {
+    block: BlockId,
+}
+
+impl Structure for Plant {
+    fn generate<'a>(
+        &self,
+        chunk: &'a mut GeneratingChunk<'a>,
+        _: &WorldRngSeeder,
+        _: &TerrainInformation,
+    ) {
+        if chunk.get_block([0, 1, 0]) == Some(BlockId::empty()) {
+            chunk.set_block([0, 1, 0], self.block);
+        }
+    }
+}
+
+pub struct BoulderFinder {
+    pub block: BlockId,
+    pub biomes: Vec<BiomeId>,
+    pub per_chunk: usize,
+}
+
+const MAX_BOULDER_RADIUS: i32 = 3;
+
+impl StructureFinder for BoulderFinder {
+    fn push_structures(
+        &self,
+        chunk: ChunkPos,
+        rand: &WorldRngSeeder,
+        t: &TerrainInformation,
+        out: &mut StructureList,
+    ) {
+        let mut rand = rand.push_num(3).rng();
+        for pos in surface_positions(chunk, &mut rand, t, self.per_chunk, &self.biomes) {
+            out.push(
+                Box::new(Boulder {
+                    block: self.block,
+                    radius: rand.gen_range(1, MAX_BOULDER_RADIUS + 1),
+                }),
+                pos,
+                self.max_bounds(),
+            );
+        }
+    }
+    fn max_bounds(&self) -> [[i32; 2]; 3] {
+        let r = MAX_BOULDER_RADIUS;
+        [[r, r], [r, r], [r, r]]
+    }
+}
+
+struct Boulder {
+    block: BlockId,
+    radius: i32,
+}
+
+impl Structure for Boulder {
+    fn generate<'a>(
+        &self,
+        chunk: &'a mut GeneratingChunk<'a>,
+        _: &WorldRngSeeder,
+        _: &TerrainInformation,
+    ) {
+        let r = self.radius;
+        for x in -r..r + 1 {
+            for y in -r..r + 1 {
+                for z in -r..r + 1 {
+                    if x * x + y * y + z * z <= r * r {
+                        chunk.set_block([x, y, z], self.block);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use geometry::Direction;
+use world::{World, BlockPos, WorldGenRng};
+use super::BlockState;
+
+/// code run for all blocks of a type
+pub trait BlockBehavior: Send + Sync {
+    /// called on the logic thread in the tick after the block in direction from the block at pos changed
+    /// changes made here notify their neighbours in the following tick
+    fn on_neighbor_changed(&self, _world: &World, _pos: BlockPos, _state: BlockState, _direction: Direction) {}
+    /// called for ticks scheduled with World::schedule_tick
+    fn on_scheduled_tick(&self, _world: &World, _pos: BlockPos, _state: BlockState) {}
+    /// called on the thread changing the block after it replaced a different block at pos
+    fn on_placed(&self, _world: &World, _pos: BlockPos, _state: BlockState) {}
+    /// called for a few randomly picked blocks of each loaded chunk every tick
+    fn on_random_tick(&self, _world: &World, _pos: BlockPos, _state: BlockState, _rng: &mut WorldGenRng) {}
+}
@@ -0,0 +1,52 @@
+use super::{StateDefinition, StateProperty};
+
+/// blocks with a fluid flow into adjacent free blocks, see world::fluid for the simulation
+#[derive(Clone, Debug)]
+pub struct Fluid {
+    /// how far the fluid flows sideways from a source
+    pub spread: u32,
+    /// ticks between a change next to the fluid and the fluid reacting to it
+    pub tick_delay: u32,
+    /// whether a flowing block between two sources becomes a source itself
+    pub renewable: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FluidLevel {
+    Source,
+    /// distance to the source, from 1 to spread
+    Flowing(u32),
+    /// fluid with the same fluid above it
+    Falling,
+}
+
+impl Fluid {
+    /// a single level property, "0" is the source, "1" to spread are flowing blocks
+    pub fn states(&self) -> StateDefinition {
+        let mut values: Vec<String> = (0..self.spread + 1).map(|i| i.to_string()).collect();
+        values.push("falling".into());
+        let values: Vec<&str> = values.iter().map(|v| &v[..]).collect();
+        StateDefinition::new(vec![StateProperty::new("level", &values)])
+    }
+
+    pub fn level(&self, meta: u32) -> FluidLevel {
+        if meta == 0 {
+            FluidLevel::Source
+        } else if meta <= self.spread {
+            FluidLevel::Flowing(meta)
+        } else {
+            FluidLevel::Falling
+        }
+    }
+
+    pub fn meta(&self, level: FluidLevel) -> u32 {
+        match level {
+            FluidLevel::Source => 0,
+            FluidLevel::Flowing(l) => {
+                assert!(l > 0 && l <= self.spread);
+                l
+            }
+            FluidLevel::Falling => self.spread + 1,
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use graphics::DrawType;
+use geometry::BlockBox;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+pub use self::state::{BlockState, StateProperty, StateDefinition};
+pub use self::fluid::{Fluid, FluidLevel};
+pub use self::behavior::BlockBehavior;
+
+mod state;
+mod fluid;
+mod behavior;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BlockId(u32);
+
+impl Default for BlockId {
+    fn default() -> BlockId {
+        BlockId::empty()
+    }
+}
+
+#[derive(Default)]
+pub struct AtomicBlockId(AtomicU32);
+
+impl AtomicBlockId {
+    pub fn new(id: BlockId) -> Self {
+        AtomicBlockId(AtomicU32::new(id.0))
+    }
+    pub fn load(&self) -> BlockId {
+        BlockId(self.0.load(Ordering::Relaxed))
+    }
+}
+
+impl BlockId {
+    pub fn empty() -> Self {
+        BlockId(0)
+    }
+    pub fn to_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum LightType {
+    Transparent,
+    Opaque,
+    Source(u8),
+    /// transparent, light passing through loses additional levels
+    Attenuating(u8),
+}
+
+impl LightType {
+    pub fn is_opaque(&self) -> bool {
+        match *self {
+            LightType::Opaque => true,
+            LightType::Transparent |
+            LightType::Source(_) |
+            LightType::Attenuating(_) => false,
+        }
+    }
+    /// levels lost by light leaving the block in addition to the usual one
+    pub fn attenuation(&self) -> u8 {
+        if let LightType::Attenuating(a) = *self { a } else { 0 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BlockDrop {
+    Itself,
+    Nothing,
+    /// name of the dropped block
+    Block(String),
+}
+
+/// gameplay properties of a block
+#[derive(Clone, Debug)]
+pub struct BlockProperties {
+    /// ticks needed to break the block, None if it cannot be broken
+    pub break_ticks: Option<u32>,
+    pub collides: bool,
+    /// fraction of the difference between current and intended velocity applied per tick while standing on the block
+    pub friction: f64,
+    /// factor applied to the movement speed of objects in or on the block
+    pub speed: f64,
+    pub drops: BlockDrop,
+    pub fluid: Option<Fluid>,
+    /// unsupported blocks fall down until they land on a colliding block
+    pub falls: bool,
+}
+
+impl Default for BlockProperties {
+    fn default() -> Self {
+        BlockProperties {
+            break_ticks: Some(10),
+            collides: true,
+            friction: 1.,
+            speed: 1.,
+            drops: BlockDrop::Itself,
+            fluid: None,
+            falls: false,
+        }
+    }
+}
+
+static FULL_BLOCK_BOXES: [BlockBox; 1] = [BlockBox::FULL];
+
+pub struct Block {
+    //one entry per state
+    draw: Vec<DrawType>,
+    light: LightType,
+    name: String,
+    properties: BlockProperties,
+    states: StateDefinition,
+    behavior: Option<Box<BlockBehavior>>,
+}
+
+impl Block {
+    pub fn new(draw: DrawType, light: LightType, name: String) -> Self {
+        Block {
+            draw: vec![draw],
+            light: light,
+            name: name,
+            properties: BlockProperties::default(),
+            states: StateDefinition::default(),
+            behavior: None,
+        }
+    }
+    /// draw is called once for each state to determine how it is drawn
+    pub fn with_states<F: FnMut(&StateDefinition, u32) -> DrawType>(mut self, states: StateDefinition, mut draw: F) -> Self {
+        self.draw = (0..states.state_count()).map(|meta| draw(&states, meta)).collect();
+        self.states = states;
+        self
+    }
+    pub fn with_properties(mut self, properties: BlockProperties) -> Self {
+        self.properties = properties;
+        self
+    }
+    pub fn with_behavior(mut self, behavior: Box<BlockBehavior>) -> Self {
+        self.behavior = Some(behavior);
+        self
+    }
+}
+
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+}
+
+impl BlockRegistry {
+    pub fn new() -> Self {
+        BlockRegistry {
+            blocks: vec![
+                Block {
+                    draw: vec![DrawType::None],
+                    light: LightType::Transparent,
+                    name: "empty".into(),
+                    properties: BlockProperties {
+                        break_ticks: None,
+                        collides: false,
+                        drops: BlockDrop::Nothing,
+                        ..Default::default()
+                    },
+                    states: StateDefinition::default(),
+                    behavior: None,
+                },
+            ],
+        }
+    }
+    pub fn add(&mut self, block: Block) -> BlockId {
+        self.blocks.push(block);
+        BlockId(self.blocks.len() as u32 - 1)
+    }
+    pub fn light_type(&self, block_id: BlockId) -> &LightType {
+        &self.blocks[block_id.0 as usize].light
+    }
+    /// states with invalid meta are drawn like the default state
+    pub fn draw_type<S: Into<BlockState>>(&self, state: S) -> &DrawType {
+        let state = state.into();
+        let draw = &self.blocks[state.id.0 as usize].draw;
+        draw.get(state.meta as usize).unwrap_or(&draw[0])
+    }
+    pub fn is_opaque_draw<S: Into<BlockState>>(&self, state: S) -> bool {
+        if let DrawType::FullOpaqueBlock(_) = *self.draw_type(state) {
+            true
+        } else {
+            false
+        }
+    }
+    pub fn is_transparent_draw<S: Into<BlockState>>(&self, state: S) -> bool {
+        if let DrawType::Transparent(_) = *self.draw_type(state) {
+            true
+        } else {
+            false
+        }
+    }
+    /// boxes objects collide with, shaped blocks collide with their boxes
+    pub fn collision_boxes(&self, state: BlockState) -> &[BlockBox] {
+        if !self.properties(state.id).collides {
+            return &[];
+        }
+        match *self.draw_type(state) {
+            DrawType::Shape(ref boxes, _) => boxes,
+            _ => &FULL_BLOCK_BOXES,
+        }
+    }
+    /// boxes the player can target
+    /// non colliding transparent blocks like water can be looked through
+    pub fn target_boxes(&self, state: BlockState) -> &[BlockBox] {
+        match *self.draw_type(state) {
+            DrawType::None => &[],
+            DrawType::Transparent(_) if !self.properties(state.id).collides => &[],
+            DrawType::Shape(ref boxes, _) => boxes,
+            _ => &FULL_BLOCK_BOXES,
+        }
+    }
+    pub fn states(&self, block_id: BlockId) -> &StateDefinition {
+        &self.blocks[block_id.0 as usize].states
+    }
+    pub fn properties(&self, block_id: BlockId) -> &BlockProperties {
+        &self.blocks[block_id.0 as usize].properties
+    }
+    pub fn behavior(&self, block_id: BlockId) -> Option<&BlockBehavior> {
+        self.blocks[block_id.0 as usize].behavior.as_ref().map(|b| &**b)
+    }
+    /// for blocks loaded from block files
+    pub fn set_behavior(&mut self, block_id: BlockId, behavior: Box<BlockBehavior>) {
+        self.blocks[block_id.0 as usize].behavior = Some(behavior);
+    }
+    /// the block an item is dropped for when block_id is broken
+    /// blocks dropping an unknown block drop nothing
+    pub fn drop(&self, block_id: BlockId) -> Option<BlockId> {
+        match self.properties(block_id).drops {
+            BlockDrop::Itself => Some(block_id),
+            BlockDrop::Nothing => None,
+            BlockDrop::Block(ref name) => self.by_name(name),
+        }
+    }
+    pub fn name(&self, block_id: BlockId) -> &str {
+        &self.blocks[block_id.0 as usize].name
+    }
+    pub fn ids(&self) -> impl Iterator<Item = BlockId> {
+        (0..self.blocks.len() as u32).map(BlockId)
+    }
+    pub fn by_name(&self, name: &str) -> Option<BlockId> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter(|&(_, block)| block.name == name)
+            .map(|(i, _)| BlockId(i as u32))
+            .next()
+    }
+}
@@ -0,0 +1,138 @@
+use super::BlockId;
+
+/// a block together with the values of its state properties
+/// meta encodes the values as described in StateDefinition
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct BlockState {
+    pub id: BlockId,
+    pub meta: u32,
+}
+
+impl BlockState {
+    /// the default state of a block
+    pub fn new(id: BlockId) -> Self {
+        BlockState { id, meta: 0 }
+    }
+    pub fn with_meta(id: BlockId, meta: u32) -> Self {
+        BlockState { id, meta }
+    }
+}
+
+impl From<BlockId> for BlockState {
+    fn from(id: BlockId) -> Self {
+        BlockState::new(id)
+    }
+}
+
+/// a named property taking one of a list of named values
+pub struct StateProperty {
+    name: String,
+    values: Vec<String>,
+}
+
+impl StateProperty {
+    /// the first value is the default
+    pub fn new(name: &str, values: &[&str]) -> Self {
+        assert!(!values.is_empty());
+        StateProperty {
+            name: name.into(),
+            values: values.iter().map(|&v| v.into()).collect(),
+        }
+    }
+    #[allow(dead_code)]
+    pub fn boolean(name: &str) -> Self {
+        Self::new(name, &["false", "true"])
+    }
+    /// values from 0 to max inclusive
+    #[allow(dead_code)]
+    pub fn int(name: &str, max: u32) -> Self {
+        StateProperty {
+            name: name.into(),
+            values: (0..max + 1).map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+/// the state properties of a block
+/// meta is a mixed radix number with one digit per property, the first property being the least significant
+#[derive(Default)]
+pub struct StateDefinition {
+    properties: Vec<StateProperty>,
+}
+
+impl StateDefinition {
+    pub fn new(properties: Vec<StateProperty>) -> Self {
+        let def = StateDefinition { properties };
+        assert!(def.properties.iter().try_fold(1u32, |n, p| n.checked_mul(p.values.len() as u32)).is_some());
+        def
+    }
+
+    /// number of distinct states
+    pub fn state_count(&self) -> u32 {
+        self.properties.iter().map(|p| p.values.len() as u32).product()
+    }
+
+    /// index of the value of a property
+    #[allow(dead_code)]
+    pub fn get(&self, meta: u32, property: &str) -> Option<u32> {
+        self.digit(property).map(|(_, divisor, count)| meta / divisor % count)
+    }
+
+    pub fn value_name(&self, meta: u32, property: &str) -> Option<&str> {
+        let (i, divisor, count) = self.digit(property)?;
+        Some(&self.properties[i].values[(meta / divisor % count) as usize])
+    }
+
+    /// returns the meta with the property changed, None if property or value are invalid
+    pub fn set(&self, meta: u32, property: &str, value: u32) -> Option<u32> {
+        let (_, divisor, count) = self.digit(property)?;
+        if value >= count {
+            return None;
+        }
+        Some(meta - (meta / divisor % count) * divisor + value * divisor)
+    }
+
+    pub fn set_named(&self, meta: u32, property: &str, value: &str) -> Option<u32> {
+        let (i, _, _) = self.digit(property)?;
+        let value = self.properties[i].values.iter().position(|v| v == value)?;
+        self.set(meta, property, value as u32)
+    }
+
+    /// property index, divisor and value count of a property
+    fn digit(&self, property: &str) -> Option<(usize, u32, u32)> {
+        let mut divisor = 1;
+        for (i, p) in self.properties.iter().enumerate() {
+            let count = p.values.len() as u32;
+            if p.name == property {
+                return Some((i, divisor, count));
+            }
+            divisor *= count;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_get() {
+        let def = StateDefinition::new(vec![
+            StateProperty::new("axis", &["y", "x", "z"]),
+            StateProperty::boolean("open"),
+            StateProperty::int("level", 7),
+        ]);
+        assert_eq!(def.state_count(), 48);
+        let meta = def.set_named(0, "axis", "z").unwrap();
+        let meta = def.set(meta, "level", 5).unwrap();
+        let meta = def.set_named(meta, "open", "true").unwrap();
+        let meta = def.set(meta, "level", 6).unwrap();
+        assert_eq!(def.value_name(meta, "axis"), Some("z"));
+        assert_eq!(def.get(meta, "open"), Some(1));
+        assert_eq!(def.get(meta, "level"), Some(6));
+        assert!(meta < def.state_count());
+        assert_eq!(def.set(meta, "level", 8), None);
+        assert_eq!(def.set_named(meta, "color", "red"), None);
+    }
+}
@@ -77,7 +77,7 @@ impl TextureLoader {
         }
     }
 
-    pub fn load<F>(self, facade: &F) -> glium::texture::CompressedSrgbTexture2dArray
+    pub fn load<F>(self, facade: &F) -> glium::texture::SrgbTexture2dArray
         where
             F: glium::backend::Facade,
     {
@@ -85,7 +85,7 @@ impl TextureLoader {
         for (n, i) in self.names {
             names[i.to_u32() as usize] = n;
         }
-        glium::texture::CompressedSrgbTexture2dArray::new(
+        glium::texture::SrgbTexture2dArray::new(
             facade,
             names.iter().map(|name| load_image(name)).collect(),
         ).unwrap()
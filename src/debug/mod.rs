@@ -1,5 +1,8 @@
 use std::io::{stdin, BufRead};
+use std::sync::{Arc, Mutex, Weak};
 use logging::root_logger;
+use world::{World, BlockPos};
+use block::BlockState;
 
 mod triggers;
 
@@ -22,6 +25,7 @@ pub enum CommandError {
 
 pub struct DebugManager {
     pub triggers: TriggerList,
+    world: Mutex<Weak<World>>,
 }
 
 impl DebugManager {
@@ -57,9 +61,19 @@ impl DebugManager {
         });
         DebugManager {
             triggers: Default::default(),
+            world: Mutex::new(Weak::new()),
         }
     }
 
+    /// set the world debug commands operate on
+    pub fn set_world(&self, world: &Arc<World>) {
+        *self.world.lock().unwrap() = Arc::downgrade(world);
+    }
+
+    fn world(&self) -> Result<Arc<World>, CommandError> {
+        self.world.lock().unwrap().upgrade().ok_or_else(|| CommandError::Custom("no world loaded".into()))
+    }
+
     pub fn run_command(cmd: &str) -> Result<(), CommandError> {
         let mut words = cmd.split_whitespace();
         match words.next() {
@@ -74,6 +88,39 @@ impl DebugManager {
                     Err(CommandError::Syntax(SYNTAX.into()))
                 }
             }
+            Some("seed") => {
+                if words.next().is_some() {
+                    return Err(CommandError::Syntax("(no arguments)".into()));
+                }
+                println!("seed: {}", GLOBAL_MANAGER.world()?.game_data().seed());
+                Ok(())
+            }
+            Some("setblock") => {
+                const SYNTAX: &str = "<x> <y> <z> <block_name> [<property>=<value> ...]";
+                let syntax = || CommandError::Syntax(SYNTAX.into());
+                let mut pos = [0; 3];
+                for p in &mut pos {
+                    *p = words.next().and_then(|w| w.parse().ok()).ok_or_else(syntax)?;
+                }
+                let world = GLOBAL_MANAGER.world()?;
+                let blocks = world.game_data().blocks();
+                let name = words.next().ok_or_else(syntax)?;
+                let id = blocks.by_name(name).ok_or_else(|| CommandError::Custom(format!("unknown block: {}", name)))?;
+                let mut meta = 0;
+                for w in words {
+                    let mut kv = w.splitn(2, '=');
+                    let (property, value) = match (kv.next(), kv.next()) {
+                        (Some(p), Some(v)) => (p, v),
+                        _ => return Err(syntax()),
+                    };
+                    meta = blocks.states(id).set_named(meta, property, value).ok_or_else(|| {
+                        CommandError::Custom(format!("invalid property or value: {}", w))
+                    })?;
+                }
+                world
+                    .set_block_state(BlockPos(pos), BlockState::with_meta(id, meta))
+                    .map_err(|()| CommandError::Custom("chunk not loaded".into()))
+            }
             Some(_) => Err(CommandError::InvalidCommand),
             None => { Ok(()) }
         }
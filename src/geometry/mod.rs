@@ -42,3 +42,32 @@ pub const CUBE_FACES: [[usize; 4]; 6] = [
     [4, 5, 6, 7],
     [1, 0, 3, 2],
 ];
+
+/// axis aligned box in block local coordinates, the whole block is 0 to 1 on each axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlockBox {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl BlockBox {
+    pub const FULL: BlockBox = BlockBox {
+        min: [0.; 3],
+        max: [1.; 3],
+    };
+
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        assert!((0..3).all(|i| 0. <= min[i] && min[i] < max[i] && max[i] <= 1.));
+        BlockBox { min, max }
+    }
+
+    /// whether the face in direction d lies on the surface of the block
+    pub fn on_block_face(&self, d: Direction) -> bool {
+        let axis = d as usize / 2;
+        if d as usize % 2 == 0 {
+            self.max[axis] == 1.
+        } else {
+            self.min[axis] == 0.
+        }
+    }
+}
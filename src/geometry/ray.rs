@@ -1,5 +1,5 @@
 use world::BlockPos;
-use geometry::Direction;
+use geometry::{Direction, BlockBox};
 
 #[derive(Debug)]
 pub struct Ray {
@@ -44,6 +44,33 @@ impl Ray {
             inverse_direction: inverse_direction,
         }
     }
+    /// distance in units of direction to the point where the ray enters the box and the face it enters through
+    /// rays starting inside the box do not intersect it
+    pub fn intersect_box(&self, pos: BlockPos, b: &BlockBox) -> Option<(f32, Direction)> {
+        let mut enter = (0f32, None);
+        let mut exit = ::std::f32::INFINITY;
+        for i in 0..3 {
+            let min = pos[i] as f32 + b.min[i];
+            let max = pos[i] as f32 + b.max[i];
+            if self.direction[i] == 0. {
+                if self.start[i] < min || self.start[i] > max {
+                    return None;
+                }
+                continue;
+            }
+            let (near, far) = if self.direction[i] > 0. { (min, max) } else { (max, min) };
+            let t_near = (near - self.start[i]) / self.direction[i];
+            let t_far = (far - self.start[i]) / self.direction[i];
+            if t_near >= enter.0 {
+                enter = (t_near, Some(Direction::from_components(i, self.direction[i] < 0.)));
+            }
+            exit = exit.min(t_far);
+        }
+        match enter {
+            (t, Some(face)) if t <= exit => Some((t, face)),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -132,4 +159,15 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn box_intersection() {
+        let slab = BlockBox::new([0.; 3], [1., 0.5, 1.]);
+        let down = Ray::new([0.5, 2., 0.5], [0., -1., 0.]);
+        assert_eq!(down.intersect_box(BlockPos([0, 0, 0]), &slab), Some((1.5, Direction::PosY)));
+        let side = Ray::new([-1., 0.25, 0.5], [1., 0., 0.]);
+        assert_eq!(side.intersect_box(BlockPos([0, 0, 0]), &slab), Some((1., Direction::NegX)));
+        let above = Ray::new([-1., 0.75, 0.5], [1., 0., 0.]);
+        assert_eq!(above.intersect_box(BlockPos([0, 0, 0]), &slab), None);
+    }
 }
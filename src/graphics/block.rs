@@ -1,7 +1,30 @@
+use geometry::BlockBox;
 use super::TextureId;
 
 #[derive(Clone)]
 pub enum DrawType {
     FullOpaqueBlock([TextureId; 6]),
+    /// alpha tested, faces between adjacent cutout blocks are drawn
+    Cutout([TextureId; 6]),
+    /// alpha blended, faces between blocks of the same type are not drawn
+    Transparent([TextureId; 6]),
+    /// alpha tested boxes, used for slabs, stairs and similar blocks
+    /// textures are mapped as if the boxes were cut out of a full block
+    Shape(Vec<BlockBox>, [TextureId; 6]),
+    /// two alpha tested quads crossing diagonally, used for plants
+    Cross(TextureId),
     None,
 }
+
+impl DrawType {
+    /// textures of the faces of draw types drawn as full cubes or boxes
+    pub fn textures(&self) -> Option<&[TextureId; 6]> {
+        match *self {
+            DrawType::FullOpaqueBlock(ref t) |
+            DrawType::Cutout(ref t) |
+            DrawType::Transparent(ref t) |
+            DrawType::Shape(_, ref t) => Some(t),
+            DrawType::Cross(_) | DrawType::None => None,
+        }
+    }
+}
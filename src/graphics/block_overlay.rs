@@ -53,12 +53,11 @@ where
         for x in (pos[0] - self.range)..(pos[0] + self.range) {
             for z in (pos[2] - self.range)..(pos[2] + self.range) {
                 for dy in 0..self.range {
-                    match self.world.get_block(BlockPos([x, pos[1] - dy, z])).map(|id| {
-                        self.world.game_data().blocks().draw_type(id)
-                    }) {
+                    let blocks = self.world.game_data().blocks();
+                    match self.world.get_block_state(BlockPos([x, pos[1] - dy, z])).map(|state| blocks.draw_type(state)) {
                         None |
-                        Some(DrawType::None) => {}
-                        Some(DrawType::FullOpaqueBlock(..)) => {
+                        Some(&DrawType::None) => {}
+                        Some(_) => {
                             faces.push((
                                 BlockPos([x, pos[1] - dy, z]),
                                 Direction::PosY,
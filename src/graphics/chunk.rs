@@ -1,11 +1,11 @@
 use std::sync::{Weak, Arc};
 use glium::*;
 use glium::uniforms::Sampler;
-use glium::texture::CompressedSrgbTexture2dArray;
+use glium::texture::SrgbTexture2dArray;
 use glium::backend::Facade;
 use glium::index::PrimitiveType;
 use world::{CHUNK_SIZE, ChunkPos, Chunk};
-use block::BlockRegistry;
+use block::{BlockRegistry, BlockState};
 use geometry::*;
 use graphics::chunk_update::ChunkRegion;
 use super::DrawType;
@@ -13,29 +13,38 @@ use super::quad;
 
 use super::{TextureId, QuadVertex};
 
+/// opaque and cutout faces are stored in the solid buffers, transparent ones separately
 pub struct RenderChunk {
     v_buf: VertexBuffer<QuadVertex>,
     i_buf: IndexBuffer<u32>,
+    transparent_v_buf: VertexBuffer<QuadVertex>,
+    transparent_i_buf: IndexBuffer<u32>,
 }
 
 pub struct RenderChunkData {
     v_buf: Vec<QuadVertex>,
     i_buf: Vec<u32>,
+    transparent_v_buf: Vec<QuadVertex>,
+    transparent_i_buf: Vec<u32>,
 }
 
+/// fragments with lower alpha are discarded in the solid pass
+pub const ALPHA_CUTOFF: f32 = 0.5;
+
 pub struct ChunkUniforms<'a> {
     pub transform: [[f32; 4]; 4],
     pub light: [f32; 3],
-    pub sampler: Sampler<'a, CompressedSrgbTexture2dArray>,
+    pub sampler: Sampler<'a, SrgbTexture2dArray>,
 }
 
 impl RenderChunkData {
     pub fn new(chunk: &ChunkRegion, blocks: &BlockRegistry, pos: ChunkPos) -> Self {
-        let vertex = Self::get_vertices(chunk, blocks, pos);
-        let index = quad::get_triangle_indices(vertex.len() / 4);
+        let (solid, transparent) = Self::get_vertices(chunk, blocks, pos);
         RenderChunkData {
-            v_buf: vertex,
-            i_buf: index,
+            i_buf: quad::get_triangle_indices(solid.len() / 4),
+            v_buf: solid,
+            transparent_i_buf: quad::get_triangle_indices(transparent.len() / 4),
+            transparent_v_buf: transparent,
         }
     }
 
@@ -43,7 +52,7 @@ impl RenderChunkData {
         region: &ChunkRegion,
         blocks: &BlockRegistry,
         pos: ChunkPos,
-    ) -> Vec<QuadVertex> {
+    ) -> (Vec<QuadVertex>, Vec<QuadVertex>) {
         let adjacent = [
             Weak::upgrade(&region.neighbours[0]),
             Weak::upgrade(&region.neighbours[1]),
@@ -53,63 +62,159 @@ impl RenderChunkData {
             Weak::upgrade(&region.neighbours[5]),
         ];
         let chunk = &*region.center;
-        let mut buffer = Vec::new();
+        let chunk_blocks = chunk.blocks();
+        let mut solid = Vec::new();
+        let mut transparent = Vec::new();
         for x in 0..CHUNK_SIZE {
             for y in 0..CHUNK_SIZE {
                 for z in 0..CHUNK_SIZE {
-                    let id = chunk.data[[x, y, z]].load();
-                    match blocks.draw_type(id) {
-                        DrawType::FullOpaqueBlock(textures) => {
+                    let state = chunk_blocks.get([x, y, z]);
+                    let draw = blocks.draw_type(state);
+                    let float_pos = [
+                        pos[0] as f32 * CHUNK_SIZE as f32 + x as f32,
+                        pos[1] as f32 * CHUNK_SIZE as f32 + y as f32,
+                        pos[2] as f32 * CHUNK_SIZE as f32 + z as f32,
+                    ];
+                    let (textures, buffer) = match *draw {
+                        DrawType::FullOpaqueBlock(ref t) | DrawType::Cutout(ref t) => (t, &mut solid),
+                        DrawType::Transparent(ref t) => (t, &mut transparent),
+                        DrawType::Shape(ref boxes, ref t) => {
+                            let mut outer_light = [None; 6];
                             for d in &ALL_DIRECTIONS {
-                                if let Some((facing_chunk, facing_index)) =
-                                Self::get_block_at(&*chunk, &adjacent, [x, y, z], *d)
-                                    {
-                                        let visible =
-                                            match blocks.draw_type(facing_chunk.data[facing_index].load()) {
-                                                DrawType::FullOpaqueBlock(_) => false,
-                                                DrawType::None => true,
-                                            };
-                                        if visible {
-                                            let float_pos =
-                                                [
-                                                    pos[0] as f32 * CHUNK_SIZE as f32 + x as f32,
-                                                    pos[1] as f32 * CHUNK_SIZE as f32 + y as f32,
-                                                    pos[2] as f32 * CHUNK_SIZE as f32 + z as f32,
-                                                ];
-                                            Self::push_face(
-                                                &mut buffer,
-                                                float_pos,
-                                                *d,
-                                                textures[*d as usize],
-                                                facing_chunk.effective_light(facing_index),
-                                            );
-                                        }
+                                if let Some((facing_chunk, facing_index)) = Self::get_block_at(&*chunk, &adjacent, [x, y, z], *d) {
+                                    let facing_block = if facing_chunk as *const Chunk == chunk as *const Chunk {
+                                        chunk_blocks.get(facing_index)
+                                    } else {
+                                        facing_chunk.block_state(facing_index)
+                                    };
+                                    if !blocks.is_opaque_draw(facing_block) {
+                                        outer_light[*d as usize] = Some(facing_chunk.effective_light(facing_index));
                                     }
+                                }
+                            }
+                            let light = chunk.effective_light([x, y, z]);
+                            Self::push_shape(&mut solid, float_pos, boxes, t, outer_light, light);
+                            continue;
+                        }
+                        DrawType::Cross(t) => {
+                            Self::push_cross(&mut solid, float_pos, t, chunk.effective_light([x, y, z]));
+                            continue;
+                        }
+                        DrawType::None => continue,
+                    };
+                    for d in &ALL_DIRECTIONS {
+                        if let Some((facing_chunk, facing_index)) = Self::get_block_at(&*chunk, &adjacent, [x, y, z], *d) {
+                            let facing_block = if facing_chunk as *const Chunk == chunk as *const Chunk {
+                                chunk_blocks.get(facing_index)
+                            } else {
+                                facing_chunk.block_state(facing_index)
+                            };
+                            if Self::face_visible(blocks, state, draw, facing_block) {
+                                Self::push_face(
+                                    buffer,
+                                    float_pos,
+                                    &BlockBox::FULL,
+                                    *d,
+                                    textures[*d as usize],
+                                    facing_chunk.effective_light(facing_index),
+                                );
                             }
                         }
-                        DrawType::None => {}
                     }
                 }
             }
         }
-        buffer
+        (solid, transparent)
     }
-    fn push_face(
+    fn face_visible(blocks: &BlockRegistry, state: BlockState, draw: &DrawType, facing: BlockState) -> bool {
+        match (draw, blocks.draw_type(facing)) {
+            (_, &DrawType::FullOpaqueBlock(_)) => false,
+            (&DrawType::Transparent(_), &DrawType::Transparent(_)) => state.id != facing.id,
+            _ => true,
+        }
+    }
+    /// outer_light is the light of the neighbour in each direction, None if the neighbour hides the faces on that side
+    /// faces inside the block are always drawn
+    fn push_shape(
         buffer: &mut Vec<QuadVertex>,
         pos: [f32; 3],
+        boxes: &[BlockBox],
+        textures: &[TextureId; 6],
+        outer_light: [Option<u8>; 6],
+        light: u8,
+    ) {
+        for d in &ALL_DIRECTIONS {
+            for b in boxes {
+                let light = if b.on_block_face(*d) {
+                    match outer_light[*d as usize] {
+                        Some(light) => light,
+                        None => continue,
+                    }
+                } else {
+                    light
+                };
+                Self::push_face(buffer, pos, b, *d, textures[*d as usize], light);
+            }
+        }
+    }
+    /// each quad is pushed with both windings so it is visible from both sides
+    fn push_cross(buffer: &mut Vec<QuadVertex>, pos: [f32; 3], texture: TextureId, light: u8) {
+        use vecmath::vec3_add;
+        const QUADS: [[[f32; 3]; 4]; 2] = [
+            [[0., 0., 0.], [1., 0., 1.], [1., 1., 1.], [0., 1., 0.]],
+            [[1., 0., 0.], [0., 0., 1.], [0., 1., 1.], [1., 1., 0.]],
+        ];
+        let tex_coords = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        for quad in &QUADS {
+            for &order in &[[0, 1, 2, 3], [1, 0, 3, 2]] {
+                for &i in &order {
+                    buffer.push(QuadVertex {
+                        position: vec3_add(pos, quad[i]),
+                        normal: [0., 1., 0.],
+                        tex_coords: tex_coords[i],
+                        texture_id: texture.to_u32() as f32,
+                        light_level: f32::from(light) / 15.,
+                    });
+                }
+            }
+        }
+    }
+    /// texture coordinates are taken from the position of the face on the block,
+    /// so faces of boxes smaller than the block show the matching part of the texture
+    pub fn push_face(
+        buffer: &mut Vec<QuadVertex>,
+        pos: [f32; 3],
+        b: &BlockBox,
         direction: Direction,
         texture: TextureId,
         light: u8,
     ) {
-        use vecmath::vec3_add;
         let vertices = CUBE_FACES[direction as usize];
-        let tex_coords = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        //texture u runs from corner 0 to 1, v from corner 1 to 2
+        let tex_axis = |from: usize, to: usize| {
+            let (a, b) = (CORNER_OFFSET[vertices[from]], CORNER_OFFSET[vertices[to]]);
+            let axis = (0..3).find(|&i| a[i] != b[i]).unwrap();
+            (axis, b[axis] > a[axis])
+        };
+        let (u_axis, v_axis) = (tex_axis(0, 1), tex_axis(1, 2));
+        let normal = direction.offset();
         for i in 0..4 {
-            let normal = direction.offset();
+            let corner = CORNER_OFFSET[vertices[i]];
+            let mut position = pos;
+            let mut local = [0.; 3];
+            for axis in 0..3 {
+                local[axis] = if corner[axis] == 0. { b.min[axis] } else { b.max[axis] };
+                position[axis] += local[axis];
+            }
+            let tex_coord = |(axis, increasing): (usize, bool)| if increasing {
+                local[axis]
+            } else {
+                1. - local[axis]
+            };
             buffer.push(QuadVertex {
-                position: vec3_add(pos, CORNER_OFFSET[vertices[i]]),
+                position: position,
                 normal: [normal[0] as f32, normal[1] as f32, normal[2] as f32],
-                tex_coords: tex_coords[i],
+                tex_coords: [tex_coord(u_axis), tex_coord(v_axis)],
                 texture_id: texture.to_u32() as f32,
                 light_level: f32::from(light) / 15.,
             });
@@ -153,12 +258,27 @@ impl RenderChunk {
         params: &DrawParameters,
         quad_shader: &Program,
     ) -> Result<(), DrawError> {
-        surface.draw(&self.v_buf, &self.i_buf, quad_shader, &uniform! {matrix:uniforms.transform,light_direction:uniforms.light,sampler:uniforms.sampler}, params)
+        surface.draw(&self.v_buf, &self.i_buf, quad_shader, &uniform! {matrix:uniforms.transform,light_direction:uniforms.light,sampler:uniforms.sampler,alpha_cutoff:ALPHA_CUTOFF}, params)
+    }
+    /// params should enable blending and disable depth writes
+    pub fn draw_transparent<S: Surface>(
+        &self,
+        surface: &mut S,
+        uniforms: &ChunkUniforms,
+        params: &DrawParameters,
+        quad_shader: &Program,
+    ) -> Result<(), DrawError> {
+        if self.transparent_i_buf.len() == 0 {
+            return Ok(());
+        }
+        surface.draw(&self.transparent_v_buf, &self.transparent_i_buf, quad_shader, &uniform! {matrix:uniforms.transform,light_direction:uniforms.light,sampler:uniforms.sampler,alpha_cutoff:0f32}, params)
     }
     pub fn new<F: Facade>(data: RenderChunkData, facade: &F) -> Self {
         RenderChunk {
             v_buf: VertexBuffer::new(facade, &data.v_buf).unwrap(),
             i_buf: IndexBuffer::new(facade, PrimitiveType::TrianglesList, &data.i_buf).unwrap(),
+            transparent_v_buf: VertexBuffer::new(facade, &data.transparent_v_buf).unwrap(),
+            transparent_i_buf: IndexBuffer::new(facade, PrimitiveType::TrianglesList, &data.transparent_i_buf).unwrap(),
         }
     }
 }
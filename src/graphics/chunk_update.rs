@@ -131,26 +131,37 @@ impl ChunkUpdateReceiver {
     }
 }
 
+/// a sender which discards all updates, for running a world without rendering it
+pub fn null_chunk_update_sender() -> ChunkUpdateSender {
+    sender::null_sender()
+}
+
 pub use self::sender::ChunkUpdateSender;
 
 mod sender {
     use super::*;
 
     pub struct ChunkUpdateSender {
-        send: Sender<ChunkUpdate>
+        send: Option<Sender<ChunkUpdate>>
     }
 
     pub fn new_sender(send: Sender<ChunkUpdate>) -> ChunkUpdateSender {
-        ChunkUpdateSender { send }
+        ChunkUpdateSender { send: Some(send) }
+    }
+
+    pub fn null_sender() -> ChunkUpdateSender {
+        ChunkUpdateSender { send: None }
     }
 
     impl ChunkUpdateSender {
         // take mutable reference to guarantee Sync safety
         pub fn send(&mut self, pos: ChunkPos, chunk: &Arc<Chunk>) {
             use std::sync::atomic::Ordering;
-            //relaxed ordering because synchronised by channel
-            if !chunk.is_in_update_queue.swap(true, Ordering::Relaxed) {
-                self.send.send(ChunkUpdate { pos, chunk: chunk.clone() }).unwrap();
+            if let Some(ref send) = self.send {
+                //relaxed ordering because synchronised by channel
+                if !chunk.is_in_update_queue.swap(true, Ordering::Relaxed) {
+                    send.send(ChunkUpdate { pos, chunk: chunk.clone() }).unwrap();
+                }
             }
         }
     }
@@ -0,0 +1,63 @@
+use glium::*;
+use glium::backend::Facade;
+use glium::index::PrimitiveType;
+use geometry::{ALL_DIRECTIONS, BlockBox};
+use world::{EntityView, EntityDraw};
+use super::chunk::{RenderChunkData, ChunkUniforms, ALPHA_CUTOFF};
+use super::quad;
+use super::QuadVertex;
+
+/// draws the entities of the world as textured boxes
+pub struct EntityRender {
+    vertices: Vec<QuadVertex>,
+}
+
+impl EntityRender {
+    pub fn new() -> Self {
+        EntityRender { vertices: Vec::new() }
+    }
+
+    pub fn draw<S: Surface, F: Facade>(
+        &mut self,
+        facade: &F,
+        surface: &mut S,
+        entities: &[EntityView],
+        uniforms: &ChunkUniforms,
+        params: &DrawParameters,
+        quad_shader: &Program,
+    ) -> Result<(), DrawError> {
+        self.vertices.clear();
+        for entity in entities {
+            let textures = match entity.draw {
+                EntityDraw::Box(textures) => textures,
+                EntityDraw::None => continue,
+            };
+            let start = self.vertices.len();
+            for &d in &ALL_DIRECTIONS {
+                RenderChunkData::push_face(
+                    &mut self.vertices,
+                    [0.; 3],
+                    &BlockBox::FULL,
+                    d,
+                    textures[d as usize],
+                    entity.light,
+                );
+            }
+            for v in &mut self.vertices[start..] {
+                for i in 0..3 {
+                    v.position[i] = (entity.position[i] + f64::from(v.position[i]) * entity.size[i]) as f32;
+                }
+            }
+        }
+        if self.vertices.is_empty() {
+            return Ok(());
+        }
+        let v_buf = VertexBuffer::new(facade, &self.vertices).unwrap();
+        let i_buf = IndexBuffer::new(
+            facade,
+            PrimitiveType::TrianglesList,
+            &quad::get_triangle_indices(self.vertices.len() / 4),
+        ).unwrap();
+        surface.draw(&v_buf, &i_buf, quad_shader, &uniform! {matrix:uniforms.transform,light_direction:uniforms.light,sampler:uniforms.sampler,alpha_cutoff:ALPHA_CUTOFF}, params)
+    }
+}
@@ -7,6 +7,7 @@ mod line;
 mod chunk;
 mod block;
 mod world;
+mod entity;
 mod virtual_display;
 #[allow(dead_code)]
 mod block_overlay;
@@ -31,7 +32,7 @@ pub use self::line::Vertex as LineVertex;
 pub use self::block_overlay::{BlockOverlay, OverlayDataSupplier, Overlay2d};
 pub use self::virtual_display::{RenderBuffer2d, VirtualDisplay, TransformedDisplay};
 pub use self::text::FontTextureHandle;
-pub use self::chunk_update::{ChunkUpdateSender, ChunkUpdateReceiver, chunk_update_channel};
+pub use self::chunk_update::{ChunkUpdateSender, ChunkUpdateReceiver, chunk_update_channel, null_chunk_update_sender};
 
 use self::quad::Vertex as QuadVertex;
 
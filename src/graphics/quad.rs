@@ -103,9 +103,13 @@ const FRAGMENT_SHADER_SRC: &str = r#"
     out vec4 color;
 
     uniform sampler2DArray sampler;
-
+    uniform float alpha_cutoff;
 
     void main() {
-        color=texture(sampler,vec3(v_tex_coords,floor(v_texture_id+0.5)))*brightness;
+        vec4 tex_color=texture(sampler,vec3(v_tex_coords,floor(v_texture_id+0.5)));
+        if(tex_color.a<alpha_cutoff){
+            discard;
+        }
+        color=vec4(tex_color.rgb*brightness,tex_color.a);
     }
 "#;
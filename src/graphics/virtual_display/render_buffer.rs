@@ -2,7 +2,7 @@ use std::borrow::Cow;
 use std::rc::Rc;
 use glium::*;
 use glium::backend::glutin::Display;
-use glium::texture::CompressedSrgbTexture2dArray;
+use glium::texture::SrgbTexture2dArray;
 use glium::backend::Facade;
 use graphics::TextureId;
 use glium::backend::Context;
@@ -97,7 +97,7 @@ impl RenderBuffer2d {
         &self,
         surface: &mut S,
         tri_shader: &Program,
-        sampler: uniforms::Sampler<CompressedSrgbTexture2dArray>,
+        sampler: uniforms::Sampler<SrgbTexture2dArray>,
         text_system: &TextSystem,
     ) {
         let v_buf = VertexBuffer::new(&self.context, &self.vertices).unwrap();
@@ -1,10 +1,11 @@
 use glium;
 use graphics::chunk::{RenderChunk, ChunkUniforms, RenderChunkData};
 use graphics::ChunkUpdateReceiver;
-use glium::texture::CompressedSrgbTexture2dArray;
+use graphics::entity::EntityRender;
+use glium::texture::SrgbTexture2dArray;
 use std::sync::mpsc::*;
 use std::collections::{HashMap, HashSet};
-use world::{CHUNK_SIZE, BlockPos, ChunkPos, chunk_at, Chunk};
+use world::{CHUNK_SIZE, BlockPos, ChunkPos, chunk_at, Chunk, EntityView};
 use rayon;
 use module::GameData;
 
@@ -18,6 +19,7 @@ pub struct WorldRender {
     player_chunk: ChunkPos,
     chunk_update_receiver: ChunkUpdateReceiver,
     game_data: GameData,
+    entity_render: EntityRender,
 }
 
 impl WorldRender {
@@ -33,13 +35,16 @@ impl WorldRender {
             player_chunk: ChunkPos([0, 0, 0]),
             chunk_update_receiver,
             game_data,
+            entity_render: EntityRender::new(),
         }
     }
-    pub fn draw<S: glium::Surface>(
-        &self,
+    pub fn draw<S: glium::Surface, F: glium::backend::Facade>(
+        &mut self,
+        facade: &F,
         surface: &mut S,
+        entities: &[EntityView],
         transform: [[f32; 4]; 4],
-        sampler: glium::uniforms::Sampler<CompressedSrgbTexture2dArray>,
+        sampler: glium::uniforms::Sampler<SrgbTexture2dArray>,
         quad_shader: &glium::Program,
     ) -> Result<(), glium::DrawError> {
         use geometry::CORNER_OFFSET;
@@ -58,7 +63,7 @@ impl WorldRender {
             light: [0., -2., 1.],
             sampler: sampler,
         };
-        let chunk_iter = self.render_chunks.iter().filter(|&(ref pos, _)| {
+        let visible: Vec<(&ChunkPos, &RenderChunk)> = self.render_chunks.iter().filter(|&(ref pos, _)| {
             let corners: Vec<[f32; 3]> = CORNER_OFFSET
                 .iter()
                 .map(|c| {
@@ -75,10 +80,27 @@ impl WorldRender {
             corners.iter().any(|c| c[2] < 1.) && corners.iter().any(|c| c[0] < 1.) &&
                 corners.iter().any(|c| c[0] > -1.) &&
                 corners.iter().any(|c| c[1] < 1.) && corners.iter().any(|c| c[1] > -1.)
-        });
-        for chunk in chunk_iter {
+        }).collect();
+        for chunk in &visible {
             chunk.1.draw(surface, &uniforms, &params, quad_shader)?;
         }
+        self.entity_render.draw(facade, surface, entities, &uniforms, &params, quad_shader)?;
+        //transparent faces are drawn after all solid ones, back to front by chunk
+        let transparent_params = glium::DrawParameters {
+            depth: glium::Depth {
+                test: glium::draw_parameters::DepthTest::IfLess,
+                write: false,
+                ..Default::default()
+            },
+            blend: glium::Blend::alpha_blending(),
+            ..Default::default()
+        };
+        let mut visible = visible;
+        let player_chunk = self.player_chunk;
+        visible.sort_unstable_by_key(|c| -c.0.square_distance(player_chunk));
+        for chunk in &visible {
+            chunk.1.draw_transparent(surface, &uniforms, &transparent_params, quad_shader)?;
+        }
         Ok(())
     }
 
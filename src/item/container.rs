@@ -0,0 +1,125 @@
+use std::any::Any;
+use std::sync::Arc;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use block::{BlockId, BlockRegistry};
+use module::GameData;
+use world::{World, BlockPos, BlockController};
+use super::*;
+use super::block_item::MAX_STACK_SIZE;
+
+/// BlockController::type_name of containers
+pub const CONTAINER_TYPE: &str = "container";
+
+/// a block controller storing items, e.g. for chests
+pub struct Container {
+    storage: SlotStorage,
+    game_data: GameData,
+}
+
+impl Container {
+    pub fn new(game_data: GameData, size: usize) -> Self {
+        Container {
+            storage: SlotStorage::new(size),
+            game_data,
+        }
+    }
+
+    pub fn storage(&self) -> &SlotStorage {
+        &self.storage
+    }
+
+    /// data restoring a container with size slots holding the stacks, for code which cannot access the GameData
+    pub fn data(blocks: &BlockRegistry, size: usize, stacks: &[(BlockId, u32)]) -> Vec<u8> {
+        assert!(stacks.len() <= size);
+        let mut out = Vec::new();
+        out.write_u32::<LittleEndian>(size as u32).unwrap();
+        for i in 0..size {
+            write_slot(&mut out, blocks, stacks.get(i).cloned());
+        }
+        out
+    }
+
+    /// the deserializer registered for CONTAINER_TYPE
+    /// stacks of blocks which no longer exist are lost
+    pub fn deserialize(game_data: &GameData, mut data: &[u8]) -> Result<Arc<BlockController>, String> {
+        let read_error = |e: ::std::io::Error| e.to_string();
+        let size = data.read_u32::<LittleEndian>().map_err(read_error)?;
+        let mut slots = Vec::new();
+        for _ in 0..size {
+            let count = data.read_u32::<LittleEndian>().map_err(read_error)?;
+            if count == 0 {
+                slots.push(Slot::new());
+                continue;
+            }
+            if count > MAX_STACK_SIZE {
+                return Err(format!("invalid stack size {}", count));
+            }
+            let len = data.read_u32::<LittleEndian>().map_err(read_error)? as usize;
+            if len > data.len() {
+                return Err("block name exceeds data".into());
+            }
+            let (name, rest) = data.split_at(len);
+            data = rest;
+            let block = ::std::str::from_utf8(name).ok().and_then(|n| game_data.blocks().by_name(n));
+            slots.push(match block {
+                Some(block) => Slot::from_itemstack(Box::new(BlockItem::new(block, count))),
+                None => Slot::new(),
+            });
+        }
+        Ok(Arc::new(Container {
+            storage: SlotStorage::from_slots(slots),
+            game_data: Arc::clone(game_data),
+        }))
+    }
+}
+
+impl BlockController for Container {
+    fn on_insert(&self) {}
+    fn on_remove(&self) {}
+    fn on_unload(&self) {}
+    fn on_load(&self) {}
+
+    fn type_name(&self) -> &'static str {
+        CONTAINER_TYPE
+    }
+
+    /// the slot count followed by a (count, block name) pair for each slot, empty slots have count 0
+    /// only block items are stored
+    fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.write_u32::<LittleEndian>(self.storage.len() as u32).unwrap();
+        for i in 0..self.storage.len() {
+            let mut lock = self.storage[i].lock();
+            let item = lock
+                .stack()
+                .and_then(|s| s.as_any().downcast_ref::<BlockItem>().map(|b| (b.block(), s.count())));
+            write_slot(&mut out, self.game_data.blocks(), item);
+        }
+        out
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    /// the contents are dropped into the world
+    fn on_block_replaced(&self, world: &World, pos: BlockPos) {
+        for i in 0..self.storage.len() {
+            if let Some(stack) = self.storage[i].take() {
+                drop_stack(world, pos, stack);
+            }
+        }
+    }
+}
+
+fn write_slot(out: &mut Vec<u8>, blocks: &BlockRegistry, item: Option<(BlockId, u32)>) {
+    match item {
+        Some((block, count)) => {
+            let name = blocks.name(block).as_bytes();
+            out.write_u32::<LittleEndian>(count).unwrap();
+            out.write_u32::<LittleEndian>(name.len() as u32).unwrap();
+            out.extend_from_slice(name);
+        }
+        None => out.write_u32::<LittleEndian>(0).unwrap(),
+    }
+}
@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::sync::Arc;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use block::BlockId;
+use graphics::DrawType;
+use module::GameData;
+use world::{World, BlockPos, Entity, EntityKind, EntityDraw};
+use super::ItemStack;
+use super::block_item::{BlockItem, MAX_STACK_SIZE};
+
+/// EntityKind::type_name of dropped blocks
+pub const DROPPED_BLOCK_TYPE: &str = "dropped_block";
+
+const SIZE: f64 = 0.25;
+
+/// block items lying in the world, picked up by players walking by
+pub struct DroppedBlock {
+    block: BlockId,
+    count: u32,
+    game_data: GameData,
+}
+
+impl DroppedBlock {
+    pub fn new(game_data: GameData, block: BlockId, count: u32) -> Self {
+        assert!(count > 0 && count <= MAX_STACK_SIZE);
+        DroppedBlock { block, count, game_data }
+    }
+
+    pub fn block(&self) -> BlockId {
+        self.block
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn set_count(&mut self, count: u32) {
+        assert!(count > 0 && count <= MAX_STACK_SIZE);
+        self.count = count;
+    }
+
+    /// the deserializer registered for DROPPED_BLOCK_TYPE
+    pub fn deserialize(game_data: &GameData, mut data: &[u8]) -> Result<Box<EntityKind>, String> {
+        let count = data.read_u32::<LittleEndian>().map_err(|e| e.to_string())?;
+        if count == 0 || count > MAX_STACK_SIZE {
+            return Err(format!("invalid stack size {}", count));
+        }
+        let name = ::std::str::from_utf8(data).map_err(|_| "block name is not utf-8".to_string())?;
+        let block = game_data
+            .blocks()
+            .by_name(name)
+            .ok_or_else(|| format!("unknown block {}", name))?;
+        Ok(Box::new(DroppedBlock::new(Arc::clone(game_data), block, count)))
+    }
+}
+
+/// spawns the items as a dropped block in the center of pos, items which are not blocks are lost
+pub fn drop_stack(world: &World, pos: BlockPos, stack: Box<ItemStack>) {
+    let block = match stack.as_any().downcast_ref::<BlockItem>() {
+        Some(item) => item.block(),
+        None => return,
+    };
+    let dropped = DroppedBlock::new(Arc::clone(world.game_data()), block, stack.count());
+    let size = dropped.size();
+    let position = [
+        f64::from(pos[0]) + (1. - size[0]) / 2.,
+        f64::from(pos[1]) + (1. - size[1]) / 2.,
+        f64::from(pos[2]) + (1. - size[2]) / 2.,
+    ];
+    world.spawn_entity(Entity::new(Box::new(dropped), position)).ok();
+}
+
+impl EntityKind for DroppedBlock {
+    fn type_name(&self) -> &'static str {
+        DROPPED_BLOCK_TYPE
+    }
+
+    /// the count followed by the block name
+    fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.write_u32::<LittleEndian>(self.count).unwrap();
+        out.extend_from_slice(self.game_data.blocks().name(self.block).as_bytes());
+        out
+    }
+
+    fn size(&self) -> [f64; 3] {
+        [SIZE; 3]
+    }
+
+    fn draw(&self) -> EntityDraw {
+        match *self.game_data.blocks().draw_type(self.block) {
+            DrawType::Cross(texture) => EntityDraw::Box([texture; 6]),
+            ref draw => draw.textures().map_or(EntityDraw::None, |t| EntityDraw::Box(*t)),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+}
@@ -5,9 +5,13 @@ use graphics::VirtualDisplay;
 
 pub use self::block_item::BlockItem;
 pub use self::storage::{SlotStorage, Slot};
+pub use self::container::{Container, CONTAINER_TYPE};
+pub use self::dropped::{DroppedBlock, DROPPED_BLOCK_TYPE, drop_stack};
 
 mod block_item;
 mod storage;
+mod container;
+mod dropped;
 
 pub trait ItemStack
 where
@@ -76,6 +76,9 @@ impl Slot {
             }
         }
     }
+    pub fn take(&self) -> Option<Box<ItemStack>> {
+        self.0.lock().unwrap().take()
+    }
     pub fn lock(&self) -> SlotLock {
         SlotLock(self.0.lock().unwrap())
     }
@@ -101,9 +104,32 @@ impl SlotStorage {
     pub fn new(size: usize) -> Self {
         SlotStorage { slots: (0..size).map(|_| Slot::new()).collect() }
     }
+    pub fn from_slots(slots: Vec<Slot>) -> Self {
+        SlotStorage { slots }
+    }
     pub fn len(&self) -> usize {
         self.slots.len()
     }
+    /// stacks the items onto existing stacks first, then into empty slots
+    /// returns the items which did not fit
+    pub fn insert(&self, game_data: &GameData, mut stack: Box<ItemStack>) -> Option<Box<ItemStack>> {
+        for slot in &self.slots {
+            if let Some(to) = slot.0.lock().unwrap().as_mut() {
+                match to.stack_from(game_data, stack, 1) {
+                    Some(rest) => stack = rest,
+                    None => return None,
+                }
+            }
+        }
+        for slot in &self.slots {
+            let mut lock = slot.0.lock().unwrap();
+            if lock.is_none() {
+                *lock = Some(stack);
+                return None;
+            }
+        }
+        Some(stack)
+    }
 }
 
 impl Index<usize> for SlotStorage {
@@ -23,6 +23,7 @@ extern crate slog_term;
 #[macro_use]
 extern crate lazy_static;
 extern crate rayon;
+extern crate byteorder;
 
 #[macro_use]
 mod logging;
@@ -38,21 +39,38 @@ mod physics;
 mod player;
 mod item;
 mod debug;
+mod options;
 
 use world::*;
 use std::sync::mpsc::channel;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 
 mod base_module;
 
+const HEADLESS_LOAD_RADIUS: i32 = 2;
+const HEADLESS_AUTOSAVE_TICKS: u64 = 20 * 60 * 5;
+
 fn main() {
-    let (game_data, textures) = module::start([base_module::module()].iter().map(|m| m.init()));
+    let options = options::Options::from_args();
+    let meta = save::WorldMeta::load_or_create(&options.world, options.seed)
+        .expect("cannot read world metadata");
+    let (game_data, textures) = module::start([base_module::module()].iter().map(|m| m.init()), meta.seed);
+    let save = save::WorldSave::open(&options.world, game_data.blocks()).expect("cannot open world directory");
+    if options.headless {
+        let world = Arc::new(World::new(game_data, graphics::null_chunk_update_sender(), save));
+        debug::manager().set_world(&world);
+        run_headless(&world, options.ticks);
+        return;
+    }
     let (send, rec) = channel();
     let (chunk_send, chunk_rec) = graphics::chunk_update_channel();
     let (display, mut events_loop) = window_util::create_window();
-    let world = Arc::new(World::new(game_data, chunk_send));
+    let world = Arc::new(World::new(game_data, chunk_send, save));
+    debug::manager().set_world(&world);
     let w2 = Arc::clone(&world);
+    let w3 = Arc::clone(&world);
     let (player_pos_rec, player_pos_send) = ui::new_position_channel();
     let player = Arc::new(player::Player::new(player_pos_send, &world, rec));
     let p2 = Arc::clone(&player);
@@ -60,16 +78,41 @@ fn main() {
         player.tick(t, w);
         TickFunctionResult::Keep
     }));
-    thread::Builder::new()
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop2 = Arc::clone(&stop);
+    let logic = thread::Builder::new()
         .name("logic".into())
         .spawn(move || {
-            loop {
-                world.flush_chunk();
-                world.run_tick();
-                world.time().next_tick();
+            while !stop2.load(Ordering::SeqCst) {
+                logic_tick(&world);
             }
         })
         .expect("cannot create main logic thread");
     let mut ui = ui::Ui::new(display, textures, send, w2, chunk_rec, p2, player_pos_rec);
     ui.run(&mut events_loop);
+    //the world must not change while it is saved
+    stop.store(true, Ordering::SeqCst);
+    logic.join().expect("logic thread panicked");
+    w3.save_loaded_chunks();
+}
+
+fn logic_tick(world: &World) {
+    world.flush_chunk();
+    world.run_tick();
+    world.time().next_tick();
+}
+
+/// run the world without a window, keeping the chunks around the origin loaded
+/// runs forever if tick_count is None
+fn run_headless(world: &World, tick_count: Option<u64>) {
+    let _load_guard = world.load_cube(ChunkPos([0; 3]), HEADLESS_LOAD_RADIUS);
+    let mut ticks = 0;
+    while tick_count.map_or(true, |max| ticks < max) {
+        logic_tick(world);
+        ticks += 1;
+        if ticks % HEADLESS_AUTOSAVE_TICKS == 0 {
+            world.save_loaded_chunks();
+        }
+    }
+    world.save_loaded_chunks();
 }
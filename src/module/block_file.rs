@@ -0,0 +1,376 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use block::{Block, BlockRegistry, LightType, BlockProperties, BlockDrop, Fluid};
+use block_texture_loader::TextureLoader;
+use geometry::Direction;
+use graphics::DrawType;
+use world::FluidBehavior;
+
+/// block definitions are stored in a subset of toml:
+/// each block is a table named after the block, containing key value pairs
+/// values may be strings, integers, floats or booleans
+///
+/// ```text
+/// [grass]
+/// texture = "grass_side"
+/// texture_top = "grass"
+/// texture_bottom = "dirt"
+/// light = "opaque"
+/// break_ticks = 15
+/// drops = "dirt"
+/// ```
+///
+/// other properties are collides, falls, friction, speed and unbreakable
+/// drops may be "nothing"
+/// draw may be "full", "cutout", "transparent", "cross" or "none",
+/// cross blocks use the texture of the positive x face
+/// light_attenuation makes transparent blocks dim light passing through them
+/// blocks with fluid_spread are fluids, fluid_ticks and fluid_renewable configure them further
+/// unbreakable = true cannot be combined with break_ticks
+///
+/// blocks cannot have states or shapes, except for the levels of fluids
+/// blocks like logs, slabs and stairs have to be added in code
+pub struct BlockFileError {
+    pub file: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for BlockFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+struct Table {
+    name: String,
+    line: usize,
+    entries: Vec<(String, usize, Value)>,
+}
+
+/// adds all blocks defined in the file to the registry
+/// blocks containing errors are skipped, all errors are returned
+pub fn load(path: &Path, blocks: &mut BlockRegistry, textures: &mut TextureLoader) -> Result<(), Vec<BlockFileError>> {
+    let error = |line, message| BlockFileError { file: path.to_owned(), line, message };
+    let text = fs::read_to_string(path).map_err(|e| vec![error(0, e.to_string())])?;
+    let (tables, parse_errors) = parse(&text);
+    let mut errors: Vec<BlockFileError> = parse_errors.into_iter().map(|(l, m)| error(l, m)).collect();
+    for table in tables {
+        if blocks.by_name(&table.name).is_some() {
+            errors.push(error(table.line, format!("block {} already exists", table.name)));
+            continue;
+        }
+        match build_block(&table, textures) {
+            Ok(block) => {
+                blocks.add(block);
+            }
+            Err(e) => errors.extend(e.into_iter().map(|(l, m)| error(l, m))),
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+fn parse(text: &str) -> (Vec<Table>, Vec<(usize, String)>) {
+    let mut tables: Vec<Table> = Vec::new();
+    let mut errors = Vec::new();
+    //keys following an invalid header are dropped instead of being added to the previous block
+    let mut skipping = false;
+    for (i, line) in text.lines().enumerate() {
+        let line_nr = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            skipping = true;
+            if !line.ends_with(']') {
+                errors.push((line_nr, "missing ]".into()));
+                continue;
+            }
+            let name = line[1..line.len() - 1].trim();
+            if !is_identifier(name) {
+                errors.push((line_nr, format!("invalid block name: {}", name)));
+            } else if tables.iter().any(|t| t.name == name) {
+                errors.push((line_nr, format!("duplicate block: {}", name)));
+            } else {
+                tables.push(Table { name: name.into(), line: line_nr, entries: Vec::new() });
+                skipping = false;
+            }
+            continue;
+        }
+        if skipping {
+            continue;
+        }
+        let mut kv = line.splitn(2, '=');
+        let (key, value) = match (kv.next().map(str::trim), kv.next().map(str::trim)) {
+            (Some(key), Some(value)) if is_identifier(key) => (key, value),
+            _ => {
+                errors.push((line_nr, format!("expected key = value: {}", line)));
+                continue;
+            }
+        };
+        let value = match parse_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                errors.push((line_nr, e));
+                continue;
+            }
+        };
+        match tables.last_mut() {
+            Some(table) => {
+                if table.entries.iter().any(|e| e.0 == key) {
+                    errors.push((line_nr, format!("duplicate key: {}", key)));
+                } else {
+                    table.entries.push((key.into(), line_nr, value));
+                }
+            }
+            None => errors.push((line_nr, "key outside of block".into())),
+        }
+    }
+    (tables, errors)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '/')
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        let content = &s[1..s.len() - 1];
+        if content.contains('"') || content.contains('\\') {
+            return Err(format!("unsupported string: {}", s));
+        }
+        Ok(Value::String(content.into()))
+    } else if s == "true" || s == "false" {
+        Ok(Value::Bool(s == "true"))
+    } else if let Ok(i) = s.parse() {
+        Ok(Value::Integer(i))
+    } else if let Ok(f) = s.parse() {
+        Ok(Value::Float(f))
+    } else {
+        Err(format!("invalid value: {}", s))
+    }
+}
+
+const FACES: [(&str, Direction); 6] = [
+    ("texture_pos_x", Direction::PosX),
+    ("texture_neg_x", Direction::NegX),
+    ("texture_pos_y", Direction::PosY),
+    ("texture_neg_y", Direction::NegY),
+    ("texture_pos_z", Direction::PosZ),
+    ("texture_neg_z", Direction::NegZ),
+];
+
+fn build_block(table: &Table, textures: &mut TextureLoader) -> Result<Block, Vec<(usize, String)>> {
+    let mut errors = Vec::new();
+    let mut texture_names: [Option<&str>; 6] = [None; 6];
+    let mut light = LightType::Opaque;
+    let mut draw = "full";
+    let mut properties = BlockProperties::default();
+    let mut attenuation = None;
+    let mut fluid = None;
+    let mut fluid_ticks = 5;
+    let mut fluid_renewable = false;
+    let mut break_ticks = None;
+    let mut unbreakable = None;
+    //texture keys are applied from least to most specific, regardless of their order in the file
+    let mut texture_keys: Vec<(usize, &[Direction], &str)> = Vec::new();
+    for &(ref key, line, ref value) in &table.entries {
+        let mut invalid = |expected: &str| errors.push((line, format!("{} must be {}", key, expected)));
+        match (&key[..], value) {
+            ("texture", &Value::String(ref t)) => texture_keys.push((0, &ALL_FACES, t)),
+            ("texture_side", &Value::String(ref t)) => texture_keys.push((1, &SIDE_FACES, t)),
+            ("texture_top", &Value::String(ref t)) => texture_keys.push((2, &[Direction::PosY], t)),
+            ("texture_bottom", &Value::String(ref t)) => texture_keys.push((2, &[Direction::NegY], t)),
+            ("light", &Value::String(ref l)) if l == "opaque" => light = LightType::Opaque,
+            ("light", &Value::String(ref l)) if l == "transparent" => light = LightType::Transparent,
+            ("light", &Value::Integer(l)) if l > 0 && l <= 15 => light = LightType::Source(l as u8),
+            ("light", _) => invalid("\"opaque\", \"transparent\" or a light level from 1 to 15"),
+            ("light_attenuation", &Value::Integer(a)) if a > 0 && a <= 15 => attenuation = Some((line, a as u8)),
+            ("light_attenuation", _) => invalid("an integer from 1 to 15"),
+            ("fluid_spread", &Value::Integer(s)) if s > 0 && s <= 15 => fluid = Some(s as u32),
+            ("fluid_spread", _) => invalid("an integer from 1 to 15"),
+            ("fluid_ticks", &Value::Integer(t)) if t > 0 && t <= i64::from(u32::max_value()) => fluid_ticks = t as u32,
+            ("fluid_ticks", _) => invalid("a positive integer"),
+            ("fluid_renewable", &Value::Bool(r)) => fluid_renewable = r,
+            ("draw", &Value::String(ref d)) if ["full", "cutout", "transparent", "cross", "none"].contains(&&d[..]) => {
+                draw = d
+            }
+            ("draw", _) => invalid("\"full\", \"cutout\", \"transparent\", \"cross\" or \"none\""),
+            ("break_ticks", &Value::Integer(t)) if t >= 0 && t <= i64::from(u32::max_value()) => {
+                break_ticks = Some((line, t as u32))
+            }
+            ("break_ticks", _) => invalid("a non negative integer"),
+            ("unbreakable", &Value::Bool(true)) => unbreakable = Some(line),
+            ("unbreakable", &Value::Bool(false)) => {}
+            ("unbreakable", _) | ("collides", _) | ("falls", _) | ("fluid_renewable", _) if !is_bool(value) => invalid("true or false"),
+            ("collides", &Value::Bool(c)) => properties.collides = c,
+            ("falls", &Value::Bool(f)) => properties.falls = f,
+            ("friction", _) => match as_float(value) {
+                Some(f) if f >= 0. && f <= 1. => properties.friction = f,
+                _ => invalid("a number from 0 to 1"),
+            },
+            ("speed", _) => match as_float(value) {
+                Some(s) if s >= 0. => properties.speed = s,
+                _ => invalid("a non negative number"),
+            },
+            ("drops", &Value::String(ref d)) if d == "nothing" => properties.drops = BlockDrop::Nothing,
+            ("drops", &Value::String(ref d)) if is_identifier(d) => properties.drops = BlockDrop::Block(d.clone()),
+            ("drops", _) => invalid("\"nothing\" or a block name"),
+            (k, &Value::String(ref t)) if FACES.iter().any(|f| f.0 == k) => {
+                let face = FACES.iter().position(|f| f.0 == k).unwrap();
+                texture_keys.push((3, &ALL_FACES[face..face + 1], t));
+            }
+            ("texture", _) | ("texture_side", _) | ("texture_top", _) | ("texture_bottom", _) => invalid("a string"),
+            (k, _) if FACES.iter().any(|f| f.0 == k) => invalid("a string"),
+            _ => errors.push((line, format!("unknown key: {}", key))),
+        }
+    }
+    match (break_ticks, unbreakable) {
+        (Some((a, _)), Some(b)) => errors.push((a.max(b), "break_ticks conflicts with unbreakable = true".into())),
+        (Some((_, t)), None) => properties.break_ticks = Some(t),
+        (None, Some(_)) => properties.break_ticks = None,
+        (None, None) => {}
+    }
+    texture_keys.sort_by_key(|k| k.0);
+    for (_, faces, texture) in texture_keys {
+        for &face in faces {
+            texture_names[face as usize] = Some(texture);
+        }
+    }
+    let draw = if draw == "none" {
+        DrawType::None
+    } else if texture_names.iter().all(Option::is_some) {
+        let mut ids = [textures.get("debug"); 6];
+        for (id, name) in ids.iter_mut().zip(texture_names.iter()) {
+            *id = textures.get(name.unwrap());
+        }
+        match draw {
+            "cutout" => DrawType::Cutout(ids),
+            "transparent" => DrawType::Transparent(ids),
+            "cross" => DrawType::Cross(ids[0]),
+            _ => DrawType::FullOpaqueBlock(ids),
+        }
+    } else {
+        errors.push((table.line, format!("block {} does not have a texture for every face", table.name)));
+        DrawType::None
+    };
+    match (attenuation, light) {
+        (None, _) => {}
+        (Some((_, a)), LightType::Transparent) => light = LightType::Attenuating(a),
+        (Some((line, _)), _) => errors.push((line, "light_attenuation requires light = \"transparent\"".into())),
+    }
+    properties.fluid = fluid.map(|spread| Fluid {
+        spread,
+        tick_delay: fluid_ticks,
+        renewable: fluid_renewable,
+    });
+    if errors.is_empty() {
+        let states = properties.fluid.as_ref().map(Fluid::states);
+        let block = Block::new(draw.clone(), light, table.name.clone()).with_properties(properties);
+        Ok(match states {
+            Some(states) => block.with_states(states, |_, _| draw.clone()).with_behavior(Box::new(FluidBehavior)),
+            None => block,
+        })
+    } else {
+        Err(errors)
+    }
+}
+
+fn is_bool(value: &Value) -> bool {
+    if let Value::Bool(_) = *value { true } else { false }
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match *value {
+        Value::Float(f) => Some(f),
+        Value::Integer(i) => Some(i as f64),
+        _ => None,
+    }
+}
+
+const ALL_FACES: [Direction; 6] = [
+    Direction::PosX,
+    Direction::NegX,
+    Direction::PosY,
+    Direction::NegY,
+    Direction::PosZ,
+    Direction::NegZ,
+];
+
+const SIDE_FACES: [Direction; 4] = [Direction::PosX, Direction::NegX, Direction::PosZ, Direction::NegZ];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_tables() {
+        let (tables, errors) = parse("# comment\n[stone]\ntexture = \"stone\" # trailing\nlight = 15\n\n[dirt]\nx = 1.5\n");
+        assert!(errors.is_empty());
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, "stone");
+        assert_eq!(tables[0].entries[0], ("texture".into(), 3, Value::String("stone".into())));
+        assert_eq!(tables[0].entries[1], ("light".into(), 4, Value::Integer(15)));
+        assert_eq!(tables[1].line, 6);
+        assert_eq!(tables[1].entries[0].2, Value::Float(1.5));
+    }
+
+    #[test]
+    fn break_ticks_and_unbreakable() {
+        let mut textures = TextureLoader::new();
+        let mut blocks = BlockRegistry::new();
+        let mut build = |text: &str| {
+            let (mut tables, errors) = parse(text);
+            assert!(errors.is_empty());
+            build_block(&tables.remove(0), &mut textures)
+        };
+        let breakable = blocks.add(build("[a]\ndraw = \"none\"\nbreak_ticks = 7\n").ok().unwrap());
+        assert_eq!(blocks.properties(breakable).break_ticks, Some(7));
+        let unbreakable = blocks.add(build("[a]\ndraw = \"none\"\nunbreakable = true\n").ok().unwrap());
+        assert_eq!(blocks.properties(unbreakable).break_ticks, None);
+        for text in &[
+            "[a]\ndraw = \"none\"\nbreak_ticks = 7\nunbreakable = true\n",
+            "[a]\ndraw = \"none\"\nunbreakable = true\nbreak_ticks = 7\n",
+        ] {
+            let errors = build(text).err().unwrap();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].0, 4);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let (tables, errors) = parse("a = 1\n[stone\n[dirt]\ntexture = dirt\ntexture\n");
+        assert_eq!(tables.len(), 1);
+        let lines: Vec<usize> = errors.iter().map(|e| e.0).collect();
+        assert_eq!(lines, vec![1, 2, 4, 5]);
+        let (tables, errors) = parse("[stone]\nlight = 1\n[stone]\nlight = 2\ndraw = \"none\"\n[dirt]\nlight = 3\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 3);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].entries, vec![("light".into(), 2, Value::Integer(1))]);
+        assert_eq!(tables[1].entries, vec![("light".into(), 7, Value::Integer(3))]);
+    }
+}
@@ -1,34 +1,51 @@
 use std::sync::Arc;
+use std::path::Path;
 use block_texture_loader::TextureLoader;
-use block::{BlockRegistry, BlockId};
+use block::{BlockRegistry, BlockId, Block, LightType};
+use graphics::DrawType;
 use world::generator::noise::NoiseParameters;
-use world::generator::overworld::{GroundGen, OverworldGenerator};
+use world::generator::overworld::{GroundGen, OverworldGenerator, CaveGen, OreGen, Climate};
 use world::generator::structure::StructureFinder;
-use world::WorldRngSeeder;
+use world::{WorldRngSeeder, BlockControllerRegistry, EntityRegistry};
+use world::save::MISSING_BLOCK;
 use world::generator::Generator;
 use world::biome::*;
 pub use self::core_textures::CoreTextureMap;
+pub use self::block_file::BlockFileError;
 
 mod core_textures;
+mod block_file;
 
 pub type GameData = Arc<GameDataInner>;
 
 pub struct GameDataInner {
     biomes: BiomeRegistry,
     block: BlockRegistry,
+    block_controllers: BlockControllerRegistry,
+    entities: EntityRegistry,
     generator: Box<Generator>,
     core_textures: CoreTextureMap,
+    seed: u64,
 }
 
-pub fn start<I: Iterator<Item = Box<Init1>>>(init1: I) -> (GameData, TextureLoader) {
+pub fn start<I: Iterator<Item = Box<Init1>>>(init1: I, seed: u64) -> (GameData, TextureLoader) {
     let mut block_registry = BlockRegistry::new();
     let mut texture_loader = TextureLoader::new();
     let mut biome_registry = BiomeRegistry::new();
+    let mut controller_registry = BlockControllerRegistry::new();
+    let mut entity_registry = EntityRegistry::new();
+    block_registry.add(Block::new(
+        DrawType::FullOpaqueBlock([texture_loader.get("debug"); 6]),
+        LightType::Opaque,
+        MISSING_BLOCK.into(),
+    ));
     let i2: Vec<Box<Init2>> = {
         let mut p1 = Phase1 {
             textures: &mut texture_loader,
             blocks: &mut block_registry,
             biomes: &mut biome_registry,
+            block_controllers: &mut controller_registry,
+            entities: &mut entity_registry,
         };
         init1.map(|m: Box<Init1>| m.run(&mut p1)).collect()
     };
@@ -38,20 +55,25 @@ pub fn start<I: Iterator<Item = Box<Init1>>>(init1: I) -> (GameData, TextureLoad
             blocks: &block_registry,
             biomes: &biome_registry,
             gen_biomes: vec![],
+            caves: vec![],
+            ores: vec![],
+            sea: None,
             structures: vec![],
         };
         let _: Vec<()> = i2.into_iter().map(|m: Box<Init2>| m.run(&mut p2)).collect();
-        p2.build(
-            block_registry.by_name("stone").unwrap(),
-            &WorldRngSeeder::new(42),
-        )
+        let mut generator = p2.build(block_registry.by_name("stone").unwrap());
+        generator.reseed(&WorldRngSeeder::new(seed));
+        generator
     };
     (
         Arc::new(GameDataInner {
             block: block_registry,
+            block_controllers: controller_registry,
+            entities: entity_registry,
             biomes: biome_registry,
             generator: Box::new(generator),
             core_textures: CoreTextureMap::new(&mut texture_loader),
+            seed,
         }),
         texture_loader,
     )
@@ -64,25 +86,46 @@ impl GameDataInner {
     pub fn blocks(&self) -> &BlockRegistry {
         &self.block
     }
+    pub fn block_controllers(&self) -> &BlockControllerRegistry {
+        &self.block_controllers
+    }
+    pub fn entities(&self) -> &EntityRegistry {
+        &self.entities
+    }
     pub fn biomes(&self) -> &BiomeRegistry {
         &self.biomes
     }
     pub fn core_textures(&self) -> &CoreTextureMap {
         &self.core_textures
     }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 }
 
 pub struct Phase1<'a> {
     pub textures: &'a mut TextureLoader,
     pub blocks: &'a mut BlockRegistry,
     pub biomes: &'a mut BiomeRegistry,
+    pub block_controllers: &'a mut BlockControllerRegistry,
+    pub entities: &'a mut EntityRegistry,
+}
+
+impl<'a> Phase1<'a> {
+    /// adds the blocks defined in a block file, see block_file for the format
+    pub fn load_block_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Vec<BlockFileError>> {
+        block_file::load(path.as_ref(), self.blocks, self.textures)
+    }
 }
 
 pub struct Phase2<'a> {
     pub textures: &'a TextureLoader,
     pub blocks: &'a BlockRegistry,
     pub biomes: &'a BiomeRegistry,
-    gen_biomes: Vec<(BiomeId, NoiseParameters, i32, GroundGen)>,
+    gen_biomes: Vec<(BiomeId, NoiseParameters, i32, GroundGen, Climate)>,
+    caves: Vec<(BiomeId, CaveGen)>,
+    ores: Vec<OreGen>,
+    sea: Option<(i32, BlockId)>,
     structures: Vec<Box<StructureFinder>>,
 }
 
@@ -93,16 +136,37 @@ impl<'a> Phase2<'a> {
         terrain: NoiseParameters,
         t_base: i32,
         layers: GroundGen,
+        climate: Climate,
     ) {
-        self.gen_biomes.push((b, terrain, t_base, layers));
+        self.gen_biomes.push((b, terrain, t_base, layers, climate));
+    }
+    /// the biome must be added using add_overworld_biome
+    pub fn set_overworld_caves(&mut self, b: BiomeId, caves: CaveGen) {
+        self.caves.push((b, caves));
+    }
+    pub fn set_sea_level(&mut self, level: i32, water: BlockId) {
+        self.sea = Some((level, water));
+    }
+    pub fn add_ore(&mut self, ore: OreGen) {
+        self.ores.push(ore);
     }
     pub fn add_structure(&mut self, s: Box<StructureFinder>) {
         self.structures.push(s);
     }
-    pub fn build(self, ground: BlockId, seeder: &WorldRngSeeder) -> OverworldGenerator {
-        let mut gen = OverworldGenerator::new(self.structures, *seeder, ground);
-        for (i, t, b, g) in self.gen_biomes {
-            gen.add_biome(i, t, b, g);
+    /// the generator should be reseeded before use
+    pub fn build(self, ground: BlockId) -> OverworldGenerator {
+        let mut gen = OverworldGenerator::new(self.structures, WorldRngSeeder::new(0), ground);
+        for (i, t, b, g, c) in self.gen_biomes {
+            gen.add_biome(i, t, b, g, c);
+        }
+        for (b, c) in self.caves {
+            gen.set_caves(b, c);
+        }
+        for o in self.ores {
+            gen.add_ore(o);
+        }
+        if let Some((level, water)) = self.sea {
+            gen.set_sea_level(level, water);
         }
         gen
     }
@@ -0,0 +1,74 @@
+use std::env;
+use std::path::PathBuf;
+
+/// settings passed on the command line
+pub struct Options {
+    pub world: PathBuf,
+    pub seed: Option<u64>,
+    pub headless: bool,
+    pub ticks: Option<u64>,
+}
+
+const USAGE: &str = "usage: block [--world <directory>] [--seed <number or text>] [--headless [--ticks <count>]]";
+
+impl Options {
+    /// exits the process if the arguments are invalid
+    pub fn from_args() -> Self {
+        match Self::parse(env::args().skip(1)) {
+            Ok(options) => options,
+            Err(e) => {
+                use std::process::exit;
+                eprintln!("{}\n{}", e, USAGE);
+                exit(2)
+            }
+        }
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Options {
+            world: PathBuf::from("world"),
+            seed: None,
+            headless: false,
+            ticks: None,
+        };
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--world" => {
+                    options.world = args.next().ok_or("missing world directory")?.into();
+                }
+                "--seed" => {
+                    options.seed = Some(parse_seed(&args.next().ok_or("missing seed")?));
+                }
+                "--headless" => {
+                    options.headless = true;
+                }
+                "--ticks" => {
+                    let ticks = args.next().ok_or("missing tick count")?;
+                    options.ticks = Some(ticks.parse().map_err(|_| format!("invalid tick count: {}", ticks))?);
+                }
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+        if options.ticks.is_some() && !options.headless {
+            return Err("--ticks requires --headless".into());
+        }
+        Ok(options)
+    }
+}
+
+/// numeric seeds are used as is, any other text is hashed
+pub fn parse_seed(seed: &str) -> u64 {
+    if let Ok(n) = seed.parse::<u64>() {
+        n
+    } else if let Ok(n) = seed.parse::<i64>() {
+        n as u64
+    } else {
+        //FNV-1a, unlike DefaultHasher guaranteed to be stable
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in seed.bytes() {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+}
@@ -1,13 +1,20 @@
 use world::{BlockPos, World};
+use geometry::BlockBox;
 use world::timekeeper::TICK_TIME;
 
 type V3 = [f64; 3];
 
+/// fraction of gravity cancelled for objects in a fluid
+const BUOYANCY: f64 = 0.8;
+/// fraction of velocity kept per tick in a fluid
+const FLUID_DRAG: f64 = 0.85;
+
 pub struct Object {
     p: V3,
     v: V3,
     size: V3,
     on_ground: bool,
+    in_fluid: bool,
 }
 
 impl Object {
@@ -18,6 +25,7 @@ impl Object {
             v: [0.; 3],
             size: size,
             on_ground: false,
+            in_fluid: false,
         }
     }
 
@@ -36,10 +44,23 @@ impl Object {
         self.on_ground
     }
 
+    /// whether the feet of the object were in a fluid during the last tick
+    pub fn in_fluid(&self) -> bool {
+        self.in_fluid
+    }
+
     pub fn tick(&mut self, collision_world: Option<&World>, gravity: bool) {
         use vecmath::*;
+        self.in_fluid = collision_world.map_or(false, |world| {
+            world
+                .get_block(self.feet_block())
+                .map_or(false, |b| world.game_data().blocks().properties(b).fluid.is_some())
+        });
         if gravity {
-            self.v[1] -= TICK_TIME * 10.;
+            self.v[1] -= TICK_TIME * 10. * if self.in_fluid { 1. - BUOYANCY } else { 1. };
+        }
+        if self.in_fluid {
+            self.v = vec3_scale(self.v, FLUID_DRAG);
         }
         if let Some(world) = collision_world {
             for i in 0..3 {
@@ -55,6 +76,30 @@ impl Object {
         self.p
     }
 
+    /// the position of the corner with the lowest coordinates
+    pub fn set_position(&mut self, p: [f64; 3]) {
+        self.p = p;
+        self.on_ground = false;
+    }
+
+    /// the block containing the bottom center of the object
+    pub fn feet_block(&self) -> BlockPos {
+        BlockPos([
+            (self.p[0] + self.size[0] / 2.).floor() as i32,
+            (self.p[1] + 1e-3).floor() as i32,
+            (self.p[2] + self.size[2] / 2.).floor() as i32,
+        ])
+    }
+
+    /// the block the object is standing on
+    pub fn ground_block(&self) -> BlockPos {
+        BlockPos([
+            (self.p[0] + self.size[0] / 2.).floor() as i32,
+            (self.p[1] - 1e-3).floor() as i32,
+            (self.p[2] + self.size[2] / 2.).floor() as i32,
+        ])
+    }
+
     fn move_axis(&mut self, axis: usize, world: &World) {
         use std::f64;
         if self.v[axis].abs() < 1e-6 {
@@ -144,7 +189,9 @@ impl Object {
     }
 }
 
-#[allow(unused_variables)]
+/// position along move_axis in block local coordinates where the front of the moving object hits the block
+/// block_bounds are the bounds swept by the object in block local coordinates
+/// unloaded blocks are solid
 fn get_block_collision(
     block_bounds: [[f64; 2]; 3],
     move_axis: usize,
@@ -152,21 +199,34 @@ fn get_block_collision(
     p: BlockPos,
     world: &World,
 ) -> f64 {
-    use block::BlockId;
     use std::f64;
-    let empty = world
-        .get_block(p)
-        .map(|b| b == BlockId::empty())
-        .unwrap_or(false);
-    if empty {
+    const EPSILON: f64 = 1e-3;
+    let blocks = world.game_data().blocks();
+    let full = [BlockBox::FULL];
+    let boxes: &[BlockBox] = match world.get_block_state(p) {
+        Some(state) => blocks.collision_boxes(state),
+        None => &full,
+    };
+    let mut collide_pos = if move_positive {
+        f64::INFINITY
+    } else {
+        f64::NEG_INFINITY
+    };
+    for b in boxes {
+        let overlaps = (0..3).filter(|&i| i != move_axis).all(|i| {
+            block_bounds[i][0] < f64::from(b.max[i]) - EPSILON && block_bounds[i][1] > f64::from(b.min[i]) + EPSILON
+        });
+        if !overlaps {
+            continue;
+        }
+        //boxes entirely behind the front of the object are ignored
         if move_positive {
-            f64::INFINITY
-        } else {
-            f64::NEG_INFINITY
+            if f64::from(b.max[move_axis]) > block_bounds[move_axis][0] + EPSILON {
+                collide_pos = collide_pos.min(f64::from(b.min[move_axis]));
+            }
+        } else if f64::from(b.min[move_axis]) < block_bounds[move_axis][1] - EPSILON {
+            collide_pos = collide_pos.max(f64::from(b.max[move_axis]));
         }
-    } else if move_positive {
-        0.
-    } else {
-        1.
     }
+    collide_pos
 }
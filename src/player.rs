@@ -2,7 +2,7 @@ use world::{World, timekeeper::TickId, LoadGuard, BlockPos, ChunkPos};
 use physics::Object as PhysObject;
 use block::BlockId;
 use geometry::ray::BlockIntersection;
-use item::{SlotStorage, Slot};
+use item::{SlotStorage, Slot, BlockItem, DroppedBlock, drop_stack};
 use std::sync::Mutex;
 use ui::{PositionUpdateSender, Message};
 use std::sync::mpsc::{Receiver, TryRecvError};
@@ -30,6 +30,13 @@ pub struct Player {
 
 pub const PLAYER_SIZE: [f64; 3] = [0.6, 1.8, 0.6];
 const PLAYER_MAX_SPEED: f64 = 4.0;
+/// upwards velocity set by jumping in a fluid
+const SWIM_SPEED: f64 = 2.5;
+/// fraction of the difference between current and intended horizontal velocity applied per tick while swimming
+const SWIM_CONTROL: f64 = 0.2;
+const LOAD_RADIUS: i32 = 3;
+/// dropped blocks within this distance of the center of the player are picked up
+const PICKUP_RADIUS: f64 = 1.5;
 
 impl Player {
     pub fn new(
@@ -44,7 +51,7 @@ impl Player {
                 movement_control: [0.0; 3],
             }),
             interface: Mutex::new(PlayerInterface {
-                chunk_load_guard: world.load_cube(ChunkPos([0; 3]), 2),
+                chunk_load_guard: world.load_cube(ChunkPos([0; 3]), LOAD_RADIUS),
                 block_target: None,
                 rec: ui_rec,
                 mouse_pressed_since: [None; 2],
@@ -93,6 +100,7 @@ impl Player {
 
     pub fn tick(&self, tick: TickId, world: &World) {
         let player_pos = self.physics_tick(tick, world);
+        self.pick_up(world);
         self.interface_tick(tick, world, player_pos);
     }
 
@@ -102,7 +110,7 @@ impl Player {
         let chunk_pos = player_pos.pos_in_chunk().0;
         let mut interface = self.interface.lock().unwrap();
         if chunk_pos != interface.chunk_load_guard.center() {
-            interface.chunk_load_guard = world.load_cube(chunk_pos, 2);
+            interface.chunk_load_guard = world.load_cube(chunk_pos, LOAD_RADIUS);
         }
         loop {
             match interface.rec.try_recv() {
@@ -123,12 +131,7 @@ impl Player {
                     }] = Some(tick);
                     if button == MouseButton::Right {
                         if let Some(ref block_target) = interface.block_target {
-                            world
-                                .set_block(
-                                    block_target.block.facing(block_target.face),
-                                    world.game_data().blocks().by_name("debug_light").unwrap(),
-                                )
-                                .is_ok();
+                            self.place_block(world, block_target);
                         }
                     }
                 }
@@ -149,30 +152,80 @@ impl Player {
 
         if let Some(block_target) = interface.block_target.clone() {
             if let Some(pressed_since) = interface.mouse_pressed_since[0] {
-                if tick.ticks_since(pressed_since) >= 10 {
-                    world
-                        .set_block(block_target.block, BlockId::empty())
-                        .is_ok();
-                }
+                self.break_tick(world, block_target.block, tick.ticks_since(pressed_since));
             } else if let Some(pressed_since) = interface.mouse_pressed_since[1] {
                 if tick.ticks_since(pressed_since) >= 10 {
-                    world
-                        .set_block(
-                            block_target.block.facing(block_target.face),
-                            world.game_data().blocks().by_name("debug_light").unwrap(),
-                        )
-                        .is_ok();
+                    self.place_block(world, &block_target);
+                }
+            }
+        }
+    }
+
+    /// places one block of the held item, or a debug light if no block is held
+    fn place_block(&self, world: &World, target: &BlockIntersection) {
+        let held = self.held_item
+            .lock()
+            .stack()
+            .and_then(|s| s.as_any().downcast_ref::<BlockItem>().map(BlockItem::block));
+        let block = held.unwrap_or_else(|| world.game_data().blocks().by_name("debug_light").unwrap());
+        if world.set_block(target.block.facing(target.face), block).is_ok() && held.is_some() {
+            Slot::new().move_some_from(world.game_data(), &self.held_item, 1);
+        }
+    }
+
+    fn break_tick(&self, world: &World, pos: BlockPos, ticks_pressed: u64) {
+        let blocks = world.game_data().blocks();
+        let block = match world.get_block(pos) {
+            Some(block) => block,
+            None => return,
+        };
+        let break_ticks = match blocks.properties(block).break_ticks {
+            Some(t) => u64::from(t),
+            None => return,
+        };
+        if ticks_pressed < break_ticks {
+            return;
+        }
+        if world.set_block(pos, BlockId::empty()).is_ok() {
+            if let Some(drop) = blocks.drop(block) {
+                if let Some(rest) = self.inventory.insert(world.game_data(), Box::new(BlockItem::new(drop, 1))) {
+                    drop_stack(world, pos, rest);
                 }
             }
         }
     }
 
+    /// moves nearby dropped blocks into the inventory
+    fn pick_up(&self, world: &World) {
+        let center = {
+            let p = self.physics.lock().unwrap().object.position();
+            [p[0] + PLAYER_SIZE[0] / 2., p[1] + PLAYER_SIZE[1] / 2., p[2] + PLAYER_SIZE[2] / 2.]
+        };
+        let taken = world.take_entities_near(center, PICKUP_RADIUS, |e| e.kind.as_any().is::<DroppedBlock>());
+        for mut entity in taken {
+            let rest = {
+                let dropped = entity.kind.as_any().downcast_ref::<DroppedBlock>().unwrap();
+                let stack = Box::new(BlockItem::new(dropped.block(), dropped.count()));
+                self.inventory.insert(world.game_data(), stack)
+            };
+            //the rest stays where it is
+            if let Some(rest) = rest {
+                entity.kind.as_any_mut().downcast_mut::<DroppedBlock>().unwrap().set_count(rest.count());
+                world.spawn_entity(entity).ok();
+            }
+        }
+    }
+
     pub fn jump(&self) {
         let mut physics = self.physics.lock().unwrap();
         if physics.object.on_ground() {
             let mut v = physics.object.v();
             v[1] = 4.8;
             physics.object.set_v(v);
+        } else if physics.object.in_fluid() {
+            let mut v = physics.object.v();
+            v[1] = v[1].max(SWIM_SPEED);
+            physics.object.set_v(v);
         }
     }
 
@@ -193,11 +246,29 @@ impl Player {
                 physics.object.tick(None, false);
                 physics.object.position()
             } else {
+                let blocks = world.game_data().blocks();
+                let feet_speed = world.get_block(physics.object.feet_block()).map_or(1., |b| blocks.properties(b).speed);
                 if physics.object.on_ground() {
-                    let mc = physics.movement_control;
-                    physics.object.set_v(mc);
+                    let ground = world
+                        .get_block(physics.object.ground_block())
+                        .map(|b| blocks.properties(b))
+                        .map_or((1., 1.), |p| (p.friction, p.speed));
+                    let target = vec3_scale(physics.movement_control, feet_speed * ground.1);
+                    let mut new_v = physics.object.v();
+                    for &i in &[0, 2] {
+                        new_v[i] += (target[i] - new_v[i]) * ground.0;
+                    }
+                    new_v[1] = target[1];
+                    physics.object.set_v(new_v);
+                } else if physics.object.in_fluid() {
+                    let target = vec3_scale(physics.movement_control, feet_speed);
+                    let mut new_v = physics.object.v();
+                    for &i in &[0, 2] {
+                        new_v[i] += (target[i] - new_v[i]) * SWIM_CONTROL;
+                    }
+                    physics.object.set_v(new_v);
                 } else {
-                    let new_v = vec3_add(physics.object.v(), vec3_scale(physics.movement_control, 1. / 256.));
+                    let new_v = vec3_add(physics.object.v(), vec3_scale(physics.movement_control, feet_speed / 256.));
                     physics.object.set_v(new_v)
                 }
                 physics.object.tick(Some(world), true);
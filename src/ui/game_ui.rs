@@ -206,6 +206,8 @@ impl GameUi {
                 let (x, y) = window_util::read_mouse_delta(&ui_core, (x, y));
                 self.player.change_look(x / 300., y / 300.);
             }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Right, .. }
+                if self.open_container(state) => {}
             WindowEvent::MouseInput { state, button, .. } => {
                 self.event_sender
                     .send(Message::MouseInput {
@@ -283,20 +285,48 @@ impl GameUi {
         }
     }
 
+    /// opens the menu of the targeted container, returns false if no container is targeted
+    fn open_container(&mut self, state: &mut UiState) -> bool {
+        use item::Container;
+        use super::menu::{ContainerMenu, MenuLayerController};
+        let controller = match self.block_target.as_ref().and_then(|t| self.world.get_block_controller(t.block)) {
+            Some(controller) => controller,
+            None => return false,
+        };
+        if !controller.upgrade().map_or(false, |c| c.as_any().is::<Container>()) {
+            return false;
+        }
+        self.player.set_movement([0.; 3]);
+        *state = UiState::Menu(Box::new(MenuLayerController::new(vec![
+            Box::new(ContainerMenu::new(
+                Arc::clone(&self.game_data),
+                Arc::clone(self.player.get_player()),
+                controller,
+            )),
+        ])));
+        true
+    }
+
     fn update_block_target(&mut self) {
         use vecmath::vec3_cast;
-        use geometry::ray::Ray;
+        use geometry::ray::{Ray, BlockIntersection};
         let ray = Ray::new(vec3_cast(self.camera.position), vec3_cast(self.camera.forward));
         let mut new_block_target = None;
         for intersect in ray.blocks().take(300) {
             let (chunk, index) = intersect.block.pos_in_chunk();
             if let Some(chunk) = self.world_render.get_chunk(chunk) {
-                match self.game_data.blocks().draw_type(chunk.data[index].load()) {
-                    DrawType::FullOpaqueBlock(_) => {
-                        new_block_target = Some(intersect);
-                        break;
-                    }
-                    DrawType::None => {}
+                let blocks = self.game_data.blocks();
+                let boxes = blocks.target_boxes(chunk.block_state(index));
+                let hit = boxes
+                    .iter()
+                    .filter_map(|b| ray.intersect_box(intersect.block, b))
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                if let Some((_, face)) = hit {
+                    new_block_target = Some(BlockIntersection {
+                        block: intersect.block,
+                        face: face,
+                    });
+                    break;
                 }
             } else {
                 break;
@@ -328,8 +358,9 @@ impl GameUi {
             let sampler = ui_core.textures.sampled().wrap_function(
                 SamplerWrapFunction::Repeat,
             );
+            let entities = self.world.entity_views();
             self.world_render
-                .draw(target, matrix, sampler, &ui_core.shader.quad)
+                .draw(&ui_core.display, target, &entities, matrix, sampler, &ui_core.shader.quad)
                 .unwrap();
             if let Some(overlay) = self.overlays.get_mut(self.current_overlay) {
                 overlay
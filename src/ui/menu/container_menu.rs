@@ -0,0 +1,201 @@
+use owning_ref::ArcRef;
+use glium::glutin::WindowEvent;
+use glium::Frame;
+use ui::ui_core::UiCore;
+use module::GameData;
+use player::Player;
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+use geometry::Rectangle;
+use item::*;
+use world::BlockController;
+use super::items::{InventoryUi, ItemSlotRender};
+use super::{Menu, EventResult};
+
+/// the storage of the container, only held while the menu renders or processes an event
+/// so the menu does not keep a removed or unloaded container alive
+struct HeldStorage {
+    storage: Option<ArcRef<BlockController, SlotStorage>>,
+    empty: SlotStorage,
+}
+
+impl Deref for HeldStorage {
+    type Target = SlotStorage;
+
+    fn deref(&self) -> &SlotStorage {
+        self.storage.as_ref().map_or(&self.empty, |s| &**s)
+    }
+}
+
+/// a container displayed above the inventory of the player
+/// the menu closes when the block controller of the container is removed
+pub struct ContainerMenu {
+    held_item_render: ItemSlotRender,
+    player: Arc<Player>,
+    game_data: GameData,
+    controller: Weak<BlockController>,
+    container: InventoryUi<HeldStorage>,
+    inventory: InventoryUi<ArcRef<Player, SlotStorage>>,
+    container_area: Rectangle<f32>,
+    inventory_area: Rectangle<f32>,
+}
+
+impl ContainerMenu {
+    pub fn new(game_data: GameData, player: Arc<Player>, controller: Weak<BlockController>) -> Self {
+        let empty = Rectangle {
+            min_y: 0.,
+            max_y: 0.01,
+            min_x: 0.,
+            max_x: 0.01,
+        };
+        ContainerMenu {
+            held_item_render: ItemSlotRender::new(),
+            player: Arc::clone(&player),
+            controller,
+            container: InventoryUi::new(
+                10,
+                Arc::clone(&game_data),
+                HeldStorage {
+                    storage: None,
+                    empty: SlotStorage::new(0),
+                },
+            ),
+            inventory: InventoryUi::new(10, Arc::clone(&game_data), ArcRef::new(player).map(|p| p.inventory())),
+            game_data,
+            container_area: empty,
+            inventory_area: empty,
+        }
+    }
+
+    /// returns false if the container no longer exists
+    fn hold(&mut self) -> bool {
+        let storage = self.controller.upgrade().and_then(|c| {
+            ArcRef::new(c)
+                .try_map(|c| c.as_any().downcast_ref::<Container>().map(Container::storage).ok_or(()))
+                .ok()
+        });
+        let held = storage.is_some();
+        self.container.storage_mut().storage = storage;
+        held
+    }
+
+    fn release(&mut self) {
+        self.container.storage_mut().storage = None;
+    }
+}
+
+impl Menu for ContainerMenu {
+    fn transparent(&self) -> bool {
+        true
+    }
+
+    fn process_event(&mut self, e: &WindowEvent, ui_core: &mut UiCore) -> EventResult {
+        use glium::glutin::*;
+        if !self.hold() {
+            return EventResult::MenuClosed;
+        }
+        let result = match *e {
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: code,
+                    ..
+                },
+                ..
+            } if code == Some(VirtualKeyCode::I) || code == Some(VirtualKeyCode::Escape) => {
+                EventResult::MenuClosed
+            }
+            WindowEvent::MouseInput {
+                button,
+                state: ElementState::Pressed,
+                ..
+            } => {
+                let in_area = |area: &Rectangle<f32>| {
+                    let pos = area.pos_to_local(ui_core.mouse_position);
+                    if pos.iter().all(|&x| x >= 0. && x <= 1.) { Some(pos) } else { None }
+                };
+                if let Some(pos) = in_area(&self.container_area) {
+                    self.container.click(pos[0], pos[1], self.player.held_item(), button);
+                } else if let Some(pos) = in_area(&self.inventory_area) {
+                    self.inventory.click(pos[0], pos[1], self.player.held_item(), button);
+                }
+                EventResult::Processed
+            }
+            _ => EventResult::Processed,
+        };
+        self.release();
+        result
+    }
+
+    fn closed(&self) -> bool {
+        self.controller.upgrade().is_none()
+    }
+
+    fn render(&mut self, ui_core: &UiCore, target: &mut Frame) {
+        if !self.hold() {
+            return;
+        }
+        use graphics::{RenderBuffer2d, VirtualDisplay};
+        use glium::uniforms::SamplerWrapFunction;
+        let sampler = ui_core.textures.sampled().wrap_function(
+            SamplerWrapFunction::Repeat,
+        );
+        {
+            let mut render_buffer = RenderBuffer2d::new(&ui_core.display);
+            let container_size = self.container.size();
+            let inv_size = self.inventory.size();
+            //one empty row between container and inventory
+            let width = container_size.0.max(inv_size.0);
+            let height = container_size.1 + 1. + inv_size.1;
+            let scale = (1. / (width / render_buffer.ui_size_x()))
+                .min(1. / (height / render_buffer.ui_size_y()))
+                .min(1.);
+            let min_y = 0.5 - height / render_buffer.ui_size_y() * scale / 2.;
+            let area = |size: (f32, f32), min_y: f32, render_buffer: &RenderBuffer2d| {
+                let hw = size.0 / render_buffer.ui_size_x() * scale / 2.;
+                Rectangle {
+                    min_y,
+                    max_y: min_y + size.1 / render_buffer.ui_size_y() * scale,
+                    min_x: 0.5 - hw,
+                    max_x: 0.5 + hw,
+                }
+            };
+            self.container_area = area(container_size, min_y, &render_buffer);
+            self.inventory_area = area(
+                inv_size,
+                self.container_area.max_y + scale / render_buffer.ui_size_y(),
+                &render_buffer,
+            );
+            {
+                let mut container_display = render_buffer.sub_display(self.container_area);
+                self.container.render(&mut container_display, ui_core);
+            }
+            {
+                let mut inventory_display = render_buffer.sub_display(self.inventory_area);
+                self.inventory.render(&mut inventory_display, ui_core);
+            }
+            render_buffer.render(
+                target,
+                &ui_core.shader.tri_2d,
+                sampler,
+                &ui_core.text_system,
+            );
+        }
+        self.release();
+        {
+            let mut render_buffer = RenderBuffer2d::new(&ui_core.display);
+            self.held_item_render.render_at_mouse(
+                self.player.held_item(),
+                &self.game_data,
+                ui_core,
+                &mut render_buffer,
+            );
+            render_buffer.render(
+                target,
+                &ui_core.shader.tri_2d,
+                sampler,
+                &ui_core.text_system,
+            );
+        }
+    }
+}
@@ -58,6 +58,10 @@ impl<T: Deref<Target = SlotStorage>> InventoryUi<T> {
         }
     }
 
+    pub fn storage_mut(&mut self) -> &mut T {
+        &mut self.storage
+    }
+
     pub fn size(&mut self) -> (f32, f32) {
         (
             self.width as f32,
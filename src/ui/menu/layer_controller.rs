@@ -55,4 +55,9 @@ impl Menu for MenuLayerController {
             m.render(ui_core, target);
         }
     }
+
+    /// all layers are closed if one of them is
+    fn closed(&self) -> bool {
+        self.layers.iter().any(|m| m.closed())
+    }
 }
@@ -3,10 +3,12 @@ use glium::Frame;
 use super::ui_core::UiCore;
 pub use self::layer_controller::MenuLayerController;
 pub use self::player_inventory::PlayerInventory;
+pub use self::container_menu::ContainerMenu;
 
 mod layer_controller;
 mod items;
 mod player_inventory;
+mod container_menu;
 
 #[must_use]
 #[allow(dead_code)]
@@ -20,4 +22,8 @@ pub trait Menu {
     fn transparent(&self) -> bool;
     fn process_event(&mut self, event: &WindowEvent, ui_core: &mut UiCore) -> EventResult;
     fn render(&mut self, &UiCore, &mut Frame);
+    /// checked every frame, the menu is closed once this returns true
+    fn closed(&self) -> bool {
+        false
+    }
 }
@@ -86,6 +86,14 @@ impl Ui {
             events.poll_events(|e| self.process_event(e));
             self.core.update();
             self.perf.action_complete();
+            let menu_closed = match self.state {
+                UiState::Menu(ref m) => m.closed(),
+                _ => false,
+            };
+            if menu_closed {
+                self.core.disable_cursor();
+                self.state = UiState::InGame;
+            }
             let draw_game = match self.state {
                 UiState::Closing => {
                     break;
@@ -1,4 +1,4 @@
-use glium::texture::CompressedSrgbTexture2dArray;
+use glium::texture::SrgbTexture2dArray;
 use block_texture_loader::TextureLoader;
 use glium::backend::glutin::Display;
 use glium_text_rusttype::TextSystem;
@@ -11,7 +11,7 @@ const DISABLE_MOUSE_GRABBING: bool = true;
 pub struct UiCore {
     pub display: Display,
     pub shader: Shader,
-    pub textures: CompressedSrgbTexture2dArray,
+    pub textures: SrgbTexture2dArray,
     pub key_state: KeyboardState,
     pub mouse_position: [f32; 2],
     pub font_texture: FontTextureHandle,
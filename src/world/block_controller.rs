@@ -1,20 +1,90 @@
 use chashmap::CHashMap;
-use std::collections::HashSet;
-use super::{ChunkPos, BlockPos};
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use super::{ChunkPos, BlockPos, World};
+use geometry::Direction;
 use std::sync::{Weak, Arc};
 use std::thread::yield_now;
 use logging::root_logger;
+use module::GameData;
 
 pub trait BlockController where Self: Send + Sync {
     fn on_insert(&self);
     fn on_remove(&self);
     fn on_unload(&self);
     fn on_load(&self);
+    /// name of the deserializer in BlockControllerRegistry which restores this controller
+    fn type_name(&self) -> &'static str;
+    /// state passed to the deserializer when the chunk is loaded again
+    fn serialize(&self) -> Vec<u8>;
+    fn as_any(&self) -> &Any;
+    /// see BlockBehavior::on_neighbor_changed
+    fn on_neighbor_changed(&self, _world: &World, _pos: BlockPos, _direction: Direction) {}
+    /// called every tick while the chunk is loaded
+    fn on_tick(&self, _world: &World, _pos: BlockPos) {}
+    /// called after on_remove if the controller was removed because its block was replaced
+    fn on_block_replaced(&self, _world: &World, _pos: BlockPos) {}
+}
+
+/// a block controller stored with its chunk
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedBlockController {
+    pub pos: BlockPos,
+    pub type_name: String,
+    pub data: Vec<u8>,
+}
+
+impl SavedBlockController {
+    fn new(pos: BlockPos, controller: &BlockController) -> Self {
+        SavedBlockController {
+            pos,
+            type_name: controller.type_name().into(),
+            data: controller.serialize(),
+        }
+    }
+}
+
+pub type Deserializer = Box<Fn(&GameData, &[u8]) -> Result<Arc<BlockController>, String> + Send + Sync>;
+
+/// deserializers of block controllers keyed by BlockController::type_name
+pub struct BlockControllerRegistry {
+    deserializers: HashMap<String, Deserializer>,
+}
+
+impl BlockControllerRegistry {
+    pub fn new() -> Self {
+        BlockControllerRegistry { deserializers: HashMap::new() }
+    }
+
+    /// panics if the name is already registered
+    pub fn register(&mut self, type_name: &str, deserializer: Deserializer) {
+        if self.deserializers.insert(type_name.into(), deserializer).is_some() {
+            panic!("block controller {} registered twice", type_name);
+        }
+    }
+
+    /// controllers which cannot be restored are logged, callers keep the saved controller
+    pub fn deserialize(&self, game_data: &GameData, saved: &SavedBlockController) -> Option<Arc<BlockController>> {
+        let result = match self.deserializers.get(&saved.type_name) {
+            Some(deserializer) => deserializer(game_data, &saved.data),
+            None => Err("unknown type".into()),
+        };
+        match result {
+            Ok(controller) => Some(controller),
+            Err(e) => {
+                error!(root_logger(), "cannot load block controller {} at {:?}: {}", saved.type_name, saved.pos, e);
+                None
+            }
+        }
+    }
 }
 
 pub struct BlockControllerMap {
     controllers: CHashMap<BlockPos, Arc<BlockController>>,
     chunks: CHashMap<ChunkPos, HashSet<BlockPos>>,
+    /// saved controllers which could not be restored, e.g. because their module was removed
+    /// they are saved again with their chunk until their block is replaced
+    unknown: CHashMap<ChunkPos, Vec<SavedBlockController>>,
 }
 
 #[allow(dead_code)]
@@ -28,17 +98,19 @@ impl BlockControllerMap {
         BlockControllerMap {
             controllers: CHashMap::new(),
             chunks: CHashMap::new(),
+            unknown: CHashMap::new(),
         }
     }
 
-    #[allow(dead_code)]
     pub fn get_block_controller(&self, pos: BlockPos) -> Option<Weak<BlockController>> {
         self.controllers.get(&pos).map(|guard| Arc::downgrade(&*guard))
     }
 
-    #[allow(dead_code)]
     pub fn kill_block_controller(&self, pos: BlockPos) -> Option<Arc<BlockController>> {
         let chunk = pos.pos_in_chunk().0;
+        if let Some(mut unknown) = self.unknown.get_mut(&chunk) {
+            unknown.retain(|c| c.pos != pos);
+        }
         if self.chunks.get_mut(&chunk)?.remove(&pos) {
             let controller = self.controllers.remove(&pos).expect("expected controller because listed in chunk");
             controller.on_remove();
@@ -48,7 +120,6 @@ impl BlockControllerMap {
         }
     }
 
-    #[allow(dead_code)]
     pub fn create_block_controller(&self, pos: BlockPos, controller: Arc<BlockController>) -> Result<(), CreateError> {
         //lock chunk entry
         if let Some(mut positions) = self.chunks.get_mut(&pos.pos_in_chunk().0) {
@@ -65,14 +136,44 @@ impl BlockControllerMap {
         }
     }
 
-    pub fn unload_chunk(&self, chunk: ChunkPos) {
+    /// returns the serialized controllers of the chunk
+    pub fn unload_chunk(&self, chunk: ChunkPos) -> Vec<SavedBlockController> {
         let positions = self.chunks.remove(&chunk).expect("chunk is loaded");
-        for p in positions {
-            self.controllers.remove(&p).unwrap().on_remove();
+        let mut saved: Vec<SavedBlockController> = positions.into_iter().map(|p| {
+            let controller = self.controllers.remove(&p).unwrap();
+            controller.on_unload();
+            SavedBlockController::new(p, &*controller)
+        }).collect();
+        saved.extend(self.unknown.remove(&chunk).unwrap_or_default());
+        saved
+    }
+
+    /// the serialized controllers of a chunk which is saved while staying loaded
+    pub fn chunk_controllers(&self, chunk: ChunkPos) -> Vec<SavedBlockController> {
+        let positions = match self.chunks.get(&chunk) {
+            Some(positions) => positions.clone(),
+            None => return Vec::new(),
+        };
+        let mut saved: Vec<SavedBlockController> = positions
+            .into_iter()
+            .filter_map(|p| self.controllers.get(&p).map(|c| SavedBlockController::new(p, &**c)))
+            .collect();
+        if let Some(unknown) = self.unknown.get(&chunk) {
+            saved.extend(unknown.iter().cloned());
         }
+        saved
     }
 
-    pub fn load_chunk<I: Iterator<Item=(BlockPos, Arc<BlockController>)>>(&self, chunk: ChunkPos, controllers: I) {
+    /// unknown are saved controllers which could not be restored
+    pub fn load_chunk<I: Iterator<Item=(BlockPos, Arc<BlockController>)>>(
+        &self,
+        chunk: ChunkPos,
+        controllers: I,
+        unknown: Vec<SavedBlockController>,
+    ) {
+        if !unknown.is_empty() {
+            self.unknown.insert(chunk, unknown);
+        }
         let mut positions = HashSet::new();
         //lock chunk entry
         self.chunks.upsert(chunk, || {
@@ -87,6 +188,24 @@ impl BlockControllerMap {
         }, |_| panic!("{:?} already loaded", chunk));
     }
 
+    /// ticks the controllers of all loaded chunks
+    /// controllers created during the tick are ticked in the next tick
+    pub fn tick(&self, world: &World) {
+        for chunk in world.chunks.loaded_chunks() {
+            let positions = match self.chunks.get(&chunk) {
+                Some(positions) => positions.clone(),
+                None => continue,
+            };
+            for pos in positions {
+                //the map must not be locked during the callback, it may create or kill controllers
+                let controller = self.controllers.get(&pos).map(|c| Arc::clone(&*c));
+                if let Some(controller) = controller {
+                    controller.on_tick(world, pos);
+                }
+            }
+        }
+    }
+
     /// crate controller entry
     /// release is called immediately after acquiring the controller entry lock
     /// chunk entry should stay locked until release is called or this function returns
@@ -111,4 +230,25 @@ impl BlockControllerMap {
             yield_now();//wait for removal to be completed
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::iter;
+
+    #[test]
+    fn unknown_controllers_are_kept() {
+        let map = BlockControllerMap::new();
+        let chunk = ChunkPos([0, 0, 0]);
+        let saved = |x| SavedBlockController {
+            pos: BlockPos([x, 0, 0]),
+            type_name: "removed".into(),
+            data: vec![x as u8],
+        };
+        map.load_chunk(chunk, iter::empty(), vec![saved(1), saved(2)]);
+        assert_eq!(map.chunk_controllers(chunk), vec![saved(1), saved(2)]);
+        assert!(map.kill_block_controller(BlockPos([1, 0, 0])).is_none());
+        assert_eq!(map.unload_chunk(chunk), vec![saved(2)]);
+        assert!(map.unknown.get(&chunk).is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use geometry::ALL_DIRECTIONS;
+use world::{World, BlockPos};
+use super::fluid;
+
+/// changes exceeding this are postponed to the next tick
+const MAX_UPDATES_PER_TICK: usize = 4096;
+
+/// changed blocks notify their neighbours in the tick after the change
+/// changes caused by the notifications are handled in the following tick, so update loops cannot stall the world
+#[derive(Default)]
+pub struct BlockUpdates {
+    queue: Mutex<Queue>,
+}
+
+#[derive(Default)]
+struct Queue {
+    order: VecDeque<BlockPos>,
+    positions: HashSet<BlockPos>,
+}
+
+impl BlockUpdates {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn block_changed(&self, pos: BlockPos) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.positions.insert(pos) {
+            queue.order.push_back(pos);
+        }
+    }
+
+    pub fn run(&self, world: &World) {
+        let changed: Vec<BlockPos> = {
+            let mut queue = self.queue.lock().unwrap();
+            let count = queue.order.len().min(MAX_UPDATES_PER_TICK);
+            let changed: Vec<BlockPos> = queue.order.drain(..count).collect();
+            for p in &changed {
+                queue.positions.remove(p);
+            }
+            changed
+        };
+        for pos in changed {
+            notify(world, pos);
+        }
+    }
+}
+
+fn notify(world: &World, pos: BlockPos) {
+    let blocks = world.game_data().blocks();
+    if let Some(state) = world.get_block_state(pos) {
+        fluid::schedule(world, pos, state);
+        world.gravity.check(blocks, pos, state);
+    }
+    for d in &ALL_DIRECTIONS {
+        let neighbor = pos.facing(*d);
+        let state = match world.get_block_state(neighbor) {
+            Some(state) => state,
+            None => continue,
+        };
+        fluid::schedule(world, neighbor, state);
+        world.gravity.check(blocks, neighbor, state);
+        if let Some(behavior) = blocks.behavior(state.id) {
+            behavior.on_neighbor_changed(world, neighbor, state, d.invert());
+        }
+        if let Some(controller) = world.block_controllers.get_block_controller(neighbor).and_then(|c| c.upgrade()) {
+            controller.on_neighbor_changed(world, neighbor, d.invert());
+        }
+    }
+}
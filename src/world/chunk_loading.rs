@@ -47,8 +47,13 @@ impl InnerMap {
         for pos in self.new_unloaded.drain() {
             map.inserter.cancel(pos);
             if map.chunks.chunk_loaded(pos) {
-                map.block_controllers.unload_chunk(pos);
-                map.chunks.remove_chunk(pos);
+                let controllers = map.block_controllers.unload_chunk(pos);
+                let entities = map.entities.unload_chunk(pos);
+                let ticks = map.scheduled_ticks.unload_chunk(pos, map.time.current_tick());
+                let falling = map.gravity.unload_chunk(pos);
+                if let Some(chunk) = map.chunks.remove_chunk(pos) {
+                    map.save_chunk(pos, &chunk, &ticks, &controllers, &entities, &falling);
+                }
             }
         }
         map.inserter.poll(map, |pos| {
@@ -1,10 +1,12 @@
 use super::atomic_light::LightState;
-use block::AtomicBlockId;
+use super::palette_array::PaletteArray;
+use block::{BlockId, BlockState};
 use num::Integer;
 use world::BlockPos;
 use std::cmp::max;
 use std::ops::{Index, IndexMut};
 use std::sync::atomic::AtomicBool;
+use std::sync::{RwLock, RwLockReadGuard};
 
 pub const CHUNK_SIZE: usize = 32;
 
@@ -45,13 +47,35 @@ impl<T> IndexMut<[usize; 3]> for ChunkArray<T> {
 }
 
 pub struct Chunk {
-    pub data: ChunkArray<AtomicBlockId>,
+    blocks: RwLock<PaletteArray<BlockState>>,
     pub artificial_light: ChunkArray<LightState>,
     pub natural_light: ChunkArray<LightState>,
     pub is_in_update_queue: AtomicBool,
 }
 
 impl Chunk {
+    pub fn new(blocks: PaletteArray<BlockState>) -> Self {
+        Chunk {
+            blocks: RwLock::new(blocks),
+            artificial_light: Default::default(),
+            natural_light: Default::default(),
+            is_in_update_queue: AtomicBool::new(false),
+        }
+    }
+    pub fn block(&self, pos: [usize; 3]) -> BlockId {
+        self.blocks.read().unwrap().get(pos).id
+    }
+    pub fn block_state(&self, pos: [usize; 3]) -> BlockState {
+        self.blocks.read().unwrap().get(pos)
+    }
+    /// returns the previous state
+    pub fn set_block_state(&self, pos: [usize; 3], state: BlockState) -> BlockState {
+        self.blocks.write().unwrap().set(pos, state)
+    }
+    /// lock the blocks for reading many of them
+    pub fn blocks(&self) -> RwLockReadGuard<PaletteArray<BlockState>> {
+        self.blocks.read().unwrap()
+    }
     pub fn effective_light(&self, pos: [usize; 3]) -> u8 {
         max(
             self.artificial_light[pos].level(),
@@ -33,8 +33,10 @@ impl RelightData {
         for b in &self.brighter {
             let own_light = lm.get_light(b.0);
             if own_light.0 > 1 {
-                let light_out = lm.compute_light_to(b.1, own_light.0);
-                queue.push(light_out, b.0.facing(b.1), Some(b.1));
+                let light_out = lm.compute_light_to(b.0, b.1, own_light.0);
+                if light_out > 0 {
+                    queue.push(light_out, b.0.facing(b.1), Some(b.1));
+                }
             }
         }
         queue
@@ -65,8 +67,9 @@ pub trait LightMap {
     fn is_opaque(&mut self, pos: BlockPos) -> bool;
     fn get_light(&mut self, pos: BlockPos) -> Light;
     fn set_light(&mut self, pos: BlockPos, Light);
+    /// light reaching the block in direction from a block at pos with the given level
     // level > 1
-    fn compute_light_to(&mut self, direction: Direction, level: u8) -> u8;
+    fn compute_light_to(&mut self, pos: BlockPos, direction: Direction, level: u8) -> u8;
     fn internal_light(&mut self, block: BlockPos) -> u8;
 }
 
@@ -103,9 +106,13 @@ pub fn relight<LM: LightMap>(lm: &mut LM, pos: BlockPos) {
         updates.push(internal_light, pos, None);
     }
     for d in &ALL_DIRECTIONS {
-        let adjacent_light = lm.get_light(pos.facing(d.invert())).0;
+        let adjacent = pos.facing(d.invert());
+        let adjacent_light = lm.get_light(adjacent).0;
         if adjacent_light > 1 {
-            updates.push(lm.compute_light_to(*d, adjacent_light), pos, Some(*d));
+            let light_in = lm.compute_light_to(adjacent, *d, adjacent_light);
+            if light_in > 0 {
+                updates.push(light_in, pos, Some(*d));
+            }
         }
     }
     increase_light(lm, updates);
@@ -145,7 +152,7 @@ pub fn increase_light<LM: LightMap>(lm: &mut LM, mut to_update: UpdateQueue) {
                 lm.set_light(current_pos.0, (level, current_pos.1));
                 if level > 1 {
                     for d in &ALL_DIRECTIONS {
-                        let adjacent_level = lm.compute_light_to(*d, level);
+                        let adjacent_level = lm.compute_light_to(current_pos.0, *d, level);
                         assert!(adjacent_level <= level);
                         if adjacent_level > 0 {
                             to_update.push(adjacent_level, current_pos.0.facing(*d), Some(*d))
@@ -170,6 +177,17 @@ impl<'a> ArtificialLightMap<'a> {
             cache: cache,
         }
     }
+    fn attenuation(&mut self, pos: BlockPos) -> u8 {
+        if self.cache.load(chunk_at(pos), self.world).is_err() {
+            0
+        } else {
+            self.world
+                .game_data
+                .blocks()
+                .light_type(self.cache.chunk().block(pos.pos_in_chunk().1))
+                .attenuation()
+        }
+    }
 }
 
 impl<'a> LightMap for ArtificialLightMap<'a> {
@@ -183,7 +201,7 @@ impl<'a> LightMap for ArtificialLightMap<'a> {
             self.world
                 .game_data
                 .blocks()
-                .light_type(self.cache.chunk().data[pos].load())
+                .light_type(self.cache.chunk().block(pos.pos_in_chunk().1))
                 .is_opaque()
         }
     }
@@ -205,19 +223,18 @@ impl<'a> LightMap for ArtificialLightMap<'a> {
         self.world.update_render(self.cache.pos());
         self.world.update_adjacent_chunks(pos);
     }
-    fn compute_light_to(&mut self, _: Direction, level: u8) -> u8 {
-        level - 1
+    fn compute_light_to(&mut self, pos: BlockPos, _: Direction, level: u8) -> u8 {
+        (level - 1).saturating_sub(self.attenuation(pos))
     }
     fn internal_light(&mut self, pos: BlockPos) -> u8 {
         if self.cache.load(chunk_at(pos), self.world).is_err() {
             0
         } else {
             match *self.world.game_data.blocks().light_type(
-                self.cache.chunk().data[pos]
-                    .load(),
+                self.cache.chunk().block(pos.pos_in_chunk().1),
             ) {
                 LightType::Source(s) => s,
-                LightType::Opaque | LightType::Transparent => 0,
+                LightType::Opaque | LightType::Transparent | LightType::Attenuating(_) => 0,
             }
         }
     }
@@ -235,6 +252,17 @@ impl<'a> NaturalLightMap<'a> {
             cache: cache,
         }
     }
+    fn attenuation(&mut self, pos: BlockPos) -> u8 {
+        if self.cache.load(chunk_at(pos), self.world).is_err() {
+            0
+        } else {
+            self.world
+                .game_data
+                .blocks()
+                .light_type(self.cache.chunk().block(pos.pos_in_chunk().1))
+                .attenuation()
+        }
+    }
 }
 
 impl<'a> LightMap for NaturalLightMap<'a> {
@@ -245,7 +273,7 @@ impl<'a> LightMap for NaturalLightMap<'a> {
             self.world
                 .game_data
                 .blocks()
-                .light_type(self.cache.chunk().data[pos].load())
+                .light_type(self.cache.chunk().block(pos.pos_in_chunk().1))
                 .is_opaque()
         }
     }
@@ -265,11 +293,12 @@ impl<'a> LightMap for NaturalLightMap<'a> {
         self.world.update_adjacent_chunks(pos);
     }
 
-    fn compute_light_to(&mut self, d: Direction, level: u8) -> u8 {
-        if level == MAX_NATURAL_LIGHT && d == Direction::NegY {
+    fn compute_light_to(&mut self, pos: BlockPos, d: Direction, level: u8) -> u8 {
+        let attenuation = self.attenuation(pos);
+        if level == MAX_NATURAL_LIGHT && d == Direction::NegY && attenuation == 0 {
             MAX_NATURAL_LIGHT
         } else {
-            level - 1
+            (level - 1).saturating_sub(attenuation)
         }
     }
 
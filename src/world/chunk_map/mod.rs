@@ -1,7 +1,8 @@
 use chashmap::{CHashMap, ReadGuard};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use num::Integer;
-use block::{BlockId, LightType};
+use block::{BlockState, LightType};
 use geometry::{Direction, ALL_DIRECTIONS};
 use module::GameData;
 use logging::*;
@@ -12,15 +13,18 @@ mod lighting;
 mod atomic_light;
 mod chunk;
 mod chunk_cache;
+mod palette_array;
 
 pub use self::position::*;
 pub use self::chunk::*;
 pub use self::chunk_cache::ChunkCache;
+pub use self::palette_array::PaletteArray;
 
 use self::lighting::*;
 
 pub struct ChunkMap {
     chunks: CHashMap<[i32; 3], Arc<Chunk>>,
+    loaded: Mutex<HashSet<ChunkPos>>,
     game_data: GameData,
     logger: Logger,
     graphics_update_sender: Mutex<ChunkUpdateSender>,
@@ -30,24 +34,29 @@ impl ChunkMap {
     pub fn new(game_data: GameData, update: ChunkUpdateSender) -> Self {
         ChunkMap {
             chunks: CHashMap::new(),
+            loaded: Mutex::new(HashSet::new()),
             game_data: game_data,
             logger: root_logger().clone(),
             graphics_update_sender: Mutex::new(update),
         }
     }
     pub fn remove_chunk(&self, pos: ChunkPos) -> Option<Arc<Chunk>> {
+        self.loaded.lock().unwrap().remove(&pos);
         self.chunks.remove(&[pos[0], pos[1], pos[2]])
     }
-    pub fn set_block(&self, pos: BlockPos, block: BlockId) -> Result<(), ()> {
+    pub fn loaded_chunks(&self) -> Vec<ChunkPos> {
+        self.loaded.lock().unwrap().iter().cloned().collect()
+    }
+    pub fn get_chunk(&self, pos: ChunkPos) -> Option<Arc<Chunk>> {
+        self.borrow_chunk(pos).map(|c| Arc::clone(&*c))
+    }
+    /// returns the previous state
+    pub fn set_block_state(&self, pos: BlockPos, state: BlockState) -> Result<BlockState, ()> {
         let chunk_pos = Self::chunk_at(pos);
         if let Some(chunk) = self.borrow_chunk(chunk_pos) {
-            let before;
-            {
-                before = chunk.data[pos].load();
-                chunk.data[pos].store(block);
-            }
-            match (*self.game_data.blocks().light_type(before),
-                   *self.game_data.blocks().light_type(block)) {
+            let before = chunk.set_block_state(pos.pos_in_chunk().1, state);
+            match (*self.game_data.blocks().light_type(before.id),
+                   *self.game_data.blocks().light_type(state.id)) {
                 (LightType::Transparent, LightType::Transparent) |
                 (LightType::Opaque, LightType::Opaque) => {}
                 (LightType::Source(s1), LightType::Source(s2)) => {
@@ -73,13 +82,21 @@ impl ChunkMap {
                     remove_and_relight(&mut self.artificial_lightmap(chunk_pos), &[pos]);
                     remove_and_relight(&mut self.natural_lightmap(chunk_pos), &[pos]);
                 }
+                (LightType::Attenuating(a1), LightType::Attenuating(a2)) if a1 == a2 => {}
+                //changes of attenuation, light passing through the block is removed and spread again
+                (LightType::Attenuating(_), _) | (_, LightType::Attenuating(_)) => {
+                    remove_and_relight(&mut self.artificial_lightmap(chunk_pos), &[pos]);
+                    remove_and_relight(&mut self.natural_lightmap(chunk_pos), &[pos]);
+                }
             }
             self.update_render(chunk_pos);
-            if self.game_data.blocks().is_opaque_draw(before) ^
-                self.game_data.blocks().is_opaque_draw(block) {
+            //faces of adjacent blocks are hidden by opaque blocks and transparent blocks of the same type
+            let blocks = self.game_data.blocks();
+            if blocks.is_opaque_draw(before) != blocks.is_opaque_draw(state) ||
+                (before.id != state.id && (blocks.is_transparent_draw(before) || blocks.is_transparent_draw(state))) {
                 self.update_adjacent_chunks(pos);
             }
-            Ok(())
+            Ok(before)
         } else {
             Err(())
         }
@@ -94,9 +111,9 @@ impl ChunkMap {
             pos[2].div_floor(&(CHUNK_SIZE as i32))])
     }
 
-    pub fn get_block(&self, pos: BlockPos) -> Option<BlockId> {
+    pub fn get_block_state(&self, pos: BlockPos) -> Option<BlockState> {
         self.borrow_chunk(Self::chunk_at(pos))
-            .map(|c| c.data[pos].load())
+            .map(|c| c.block_state(pos.pos_in_chunk().1))
     }
     pub fn natural_light(&self, pos: BlockPos) -> Option<(u8, Option<Direction>)> {
         if let Some(chunk) = self.borrow_chunk(Self::chunk_at(pos)) {
@@ -118,6 +135,7 @@ impl ChunkMap {
     pub fn insert_chunk(&self, insert_pos: ChunkPos, chunk: Arc<Chunk>, light_sources: &[(BlockPos, u8)]) {
         let mut sources_to_trigger = UpdateQueue::new();
         self.chunks.insert([insert_pos[0], insert_pos[1], insert_pos[2]], chunk);
+        self.loaded.lock().unwrap().insert(insert_pos);
         for source in light_sources {
             sources_to_trigger.push(source.1, source.0, None);
         }
@@ -201,7 +219,8 @@ impl ChunkMap {
             Direction::NegZ => (false, 0, 1, 2),
         };
 
-        let mut brightness = [[(0, 0); CHUNK_SIZE]; CHUNK_SIZE];
+        //light levels and attenuation of the blocks on the face
+        let mut brightness = [[(0, 0, 0); CHUNK_SIZE]; CHUNK_SIZE];
         let chunk = match self.borrow_chunk(pos) {
             Some(chunk) => chunk,
             None => {
@@ -219,6 +238,7 @@ impl ChunkMap {
                 block_pos[face_direction] = if positive { CHUNK_SIZE - 1 } else { 0 };
                 brightness.0 = chunk.artificial_light[block_pos].level();
                 brightness.1 = chunk.natural_light[block_pos].level();
+                brightness.2 = self.game_data.blocks().light_type(chunk.block(block_pos)).attenuation();
             }
         }
         let chunk_size = CHUNK_SIZE as i32;
@@ -231,15 +251,13 @@ impl ChunkMap {
                 block_pos.0[d2] += j as i32;
                 block_pos.0[face_direction] += if positive { CHUNK_SIZE as i32 } else { -1 };
 
-                if brightness.0 > 1 {
-                    artificial_updates.push(brightness.0 - 1, block_pos, Some(face));
+                if brightness.0 > brightness.2 + 1 {
+                    artificial_updates.push(brightness.0 - 1 - brightness.2, block_pos, Some(face));
                 }
-                if brightness.1 > 1 {
-                    if face == Direction::NegY && brightness.1 == MAX_NATURAL_LIGHT {
-                        natural_updates.push(MAX_NATURAL_LIGHT, block_pos, Some(face));
-                    } else {
-                        natural_updates.push(brightness.1 - 1, block_pos, Some(face));
-                    }
+                if face == Direction::NegY && brightness.1 == MAX_NATURAL_LIGHT && brightness.2 == 0 {
+                    natural_updates.push(MAX_NATURAL_LIGHT, block_pos, Some(face));
+                } else if brightness.1 > brightness.2 + 1 {
+                    natural_updates.push(brightness.1 - 1 - brightness.2, block_pos, Some(face));
                 }
             }
         }
@@ -0,0 +1,152 @@
+use world::CHUNK_SIZE;
+
+const VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// chunk sized array storing indices into a palette of the distinct values it contains
+/// indices are bit packed, using the smallest power of two number of bits that fits the palette
+/// an array containing only a single value stores no indices at all
+pub struct PaletteArray<T> {
+    palette: Vec<T>,
+    //number of positions using each palette entry, entries with count 0 may be reused
+    counts: Vec<u32>,
+    bits: usize,
+    words: Vec<u64>,
+}
+
+fn linear_index(pos: [usize; 3]) -> usize {
+    debug_assert!(pos.iter().all(|&x| x < CHUNK_SIZE));
+    (pos[0] * CHUNK_SIZE + pos[1]) * CHUNK_SIZE + pos[2]
+}
+
+impl<T: Copy + Eq> PaletteArray<T> {
+    pub fn new(value: T) -> Self {
+        PaletteArray {
+            palette: vec![value],
+            counts: vec![VOLUME as u32],
+            bits: 0,
+            words: Vec::new(),
+        }
+    }
+
+    pub fn from_fn<F: FnMut([usize; 3]) -> T>(mut f: F) -> Self {
+        let mut ret = Self::new(f([0, 0, 0]));
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    ret.set([x, y, z], f([x, y, z]));
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn get(&self, pos: [usize; 3]) -> T {
+        self.palette[self.palette_index(linear_index(pos))]
+    }
+
+    /// returns the previous value
+    pub fn set(&mut self, pos: [usize; 3], value: T) -> T {
+        let index = linear_index(pos);
+        let old_palette_index = self.palette_index(index);
+        let old = self.palette[old_palette_index];
+        if old == value {
+            return old;
+        }
+        let new_palette_index = self.palette_entry(value);
+        self.counts[old_palette_index] -= 1;
+        self.counts[new_palette_index] += 1;
+        if self.counts[new_palette_index] as usize == VOLUME {
+            *self = Self::new(value);
+        } else {
+            self.store_index(index, new_palette_index);
+        }
+        old
+    }
+
+    /// number of distinct values which have been stored since the palette was last reset
+    #[allow(dead_code)]
+    pub fn palette_len(&self) -> usize {
+        self.palette.len()
+    }
+
+    fn palette_index(&self, index: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits;
+        let shift = (index % per_word) * self.bits;
+        ((self.words[index / per_word] >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    fn store_index(&mut self, index: usize, palette_index: usize) {
+        Self::store_packed(&mut self.words, self.bits, index, palette_index);
+    }
+
+    fn store_packed(words: &mut [u64], bits: usize, index: usize, palette_index: usize) {
+        let per_word = 64 / bits;
+        let shift = (index % per_word) * bits;
+        let mask = ((1u64 << bits) - 1) << shift;
+        let word = &mut words[index / per_word];
+        *word = (*word & !mask) | ((palette_index as u64) << shift);
+    }
+
+    /// finds or creates a palette entry for value, growing the index size if necessary
+    fn palette_entry(&mut self, value: T) -> usize {
+        if let Some(i) = self.palette.iter().position(|&v| v == value) {
+            return i;
+        }
+        if let Some(i) = self.counts.iter().position(|&c| c == 0) {
+            self.palette[i] = value;
+            return i;
+        }
+        self.palette.push(value);
+        self.counts.push(0);
+        if self.palette.len() > (1 << self.bits) {
+            self.grow();
+        }
+        self.palette.len() - 1
+    }
+
+    fn grow(&mut self) {
+        let new_bits = if self.bits == 0 { 1 } else { self.bits * 2 };
+        let mut words = vec![0; VOLUME / (64 / new_bits)];
+        for i in 0..VOLUME {
+            Self::store_packed(&mut words, new_bits, i, self.palette_index(i));
+        }
+        self.words = words;
+        self.bits = new_bits;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_get() {
+        let mut array = PaletteArray::new(0u32);
+        for i in 0..CHUNK_SIZE {
+            assert_eq!(array.set([i, i, 0], i as u32 + 1), 0);
+        }
+        assert_eq!(array.palette_len(), CHUNK_SIZE + 1);
+        for i in 0..CHUNK_SIZE {
+            assert_eq!(array.get([i, i, 0]), i as u32 + 1);
+            assert_eq!(array.get([i, i, 1]), 0);
+        }
+    }
+
+    #[test]
+    fn uniform_resets_palette() {
+        let mut array = PaletteArray::from_fn(|p| p[1] as u32);
+        assert_eq!(array.palette_len(), CHUNK_SIZE);
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    array.set([x, y, z], 7);
+                }
+            }
+        }
+        assert_eq!(array.palette_len(), 1);
+        assert_eq!(array.get([3, 4, 5]), 7);
+    }
+}
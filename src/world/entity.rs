@@ -0,0 +1,297 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+use graphics::TextureId;
+use logging::root_logger;
+use module::GameData;
+use physics::Object;
+use world::{World, ChunkPos, chunk_at};
+
+/// identifies an entity while the world is running, ids are not saved
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct EntityId(u64);
+
+/// how the renderer draws an entity
+#[derive(Clone, Copy, Debug)]
+pub enum EntityDraw {
+    None,
+    /// a box of the size of the body
+    Box([TextureId; 6]),
+}
+
+/// the type specific part of an entity
+pub trait EntityKind: Send {
+    /// name of the deserializer in EntityRegistry which restores this entity
+    fn type_name(&self) -> &'static str;
+    /// state passed to the deserializer when the chunk is loaded again, the body is saved separately
+    fn serialize(&self) -> Vec<u8>;
+    /// size of the physics body
+    fn size(&self) -> [f64; 3];
+    fn draw(&self) -> EntityDraw;
+    /// called every tick before the body moves, returns false if the entity should be removed
+    /// the entities of the world cannot be accessed during this call
+    fn tick(&mut self, _world: &World, _id: EntityId, _body: &mut Object) -> bool {
+        true
+    }
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+}
+
+/// a moving object which is not a block
+pub struct Entity {
+    pub kind: Box<EntityKind>,
+    pub body: Object,
+}
+
+impl Entity {
+    pub fn new(kind: Box<EntityKind>, position: [f64; 3]) -> Self {
+        let mut body = Object::new(kind.size());
+        body.set_position(position);
+        Entity { kind, body }
+    }
+
+    fn chunk(&self) -> ChunkPos {
+        chunk_at(self.body.feet_block())
+    }
+}
+
+/// an entity stored with its chunk
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedEntity {
+    pub type_name: String,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+    pub data: Vec<u8>,
+}
+
+impl SavedEntity {
+    fn new(entity: &Entity) -> Self {
+        SavedEntity {
+            type_name: entity.kind.type_name().into(),
+            position: entity.body.position(),
+            velocity: entity.body.v(),
+            data: entity.kind.serialize(),
+        }
+    }
+}
+
+pub type EntityDeserializer = Box<Fn(&GameData, &[u8]) -> Result<Box<EntityKind>, String> + Send + Sync>;
+
+/// deserializers of entities keyed by EntityKind::type_name
+pub struct EntityRegistry {
+    deserializers: HashMap<String, EntityDeserializer>,
+}
+
+impl EntityRegistry {
+    pub fn new() -> Self {
+        EntityRegistry { deserializers: HashMap::new() }
+    }
+
+    /// panics if the name is already registered
+    pub fn register(&mut self, type_name: &str, deserializer: EntityDeserializer) {
+        if self.deserializers.insert(type_name.into(), deserializer).is_some() {
+            panic!("entity {} registered twice", type_name);
+        }
+    }
+
+    /// entities which cannot be restored are logged, callers keep the saved entity
+    pub fn deserialize(&self, game_data: &GameData, saved: &SavedEntity) -> Option<Entity> {
+        let result = match self.deserializers.get(&saved.type_name) {
+            Some(deserializer) => deserializer(game_data, &saved.data),
+            None => Err("unknown type".into()),
+        };
+        match result {
+            Ok(kind) => {
+                let mut entity = Entity::new(kind, saved.position);
+                entity.body.set_v(saved.velocity);
+                Some(entity)
+            }
+            Err(e) => {
+                error!(root_logger(), "cannot load entity {} at {:?}: {}", saved.type_name, saved.position, e);
+                None
+            }
+        }
+    }
+}
+
+/// what the renderer needs to know about an entity
+#[derive(Clone, Copy, Debug)]
+pub struct EntityView {
+    #[allow(dead_code)]
+    pub id: EntityId,
+    pub position: [f64; 3],
+    pub size: [f64; 3],
+    pub draw: EntityDraw,
+    pub light: u8,
+}
+
+/// entities are stored in the chunk containing their feet
+/// entities moving into a chunk which is not loaded stay in their previous chunk
+pub struct EntityMap {
+    inner: Mutex<Inner>,
+    /// held while entities are taken out of the map to be ticked
+    /// saving and unloading wait for it, so they see all entities of the chunk
+    ticking: Mutex<()>,
+    /// updated at the end of each tick
+    views: Mutex<Vec<EntityView>>,
+}
+
+struct Inner {
+    next_id: u64,
+    by_chunk: HashMap<ChunkPos, Vec<(EntityId, Entity)>>,
+    /// saved entities which could not be restored, e.g. because their module was removed
+    /// they are saved again with their chunk
+    unknown: HashMap<ChunkPos, Vec<SavedEntity>>,
+}
+
+impl EntityMap {
+    pub fn new() -> Self {
+        EntityMap {
+            inner: Mutex::new(Inner {
+                next_id: 0,
+                by_chunk: HashMap::new(),
+                unknown: HashMap::new(),
+            }),
+            ticking: Mutex::new(()),
+            views: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// fails if the chunk of the entity is not loaded
+    pub fn spawn(&self, entity: Entity) -> Result<EntityId, Entity> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = EntityId(inner.next_id);
+        match inner.by_chunk.get_mut(&entity.chunk()) {
+            Some(entities) => entities.push((id, entity)),
+            None => return Err(entity),
+        }
+        inner.next_id += 1;
+        Ok(id)
+    }
+
+    /// removes all entities within radius of position for which filter returns true
+    pub fn take_near<F: FnMut(&Entity) -> bool>(&self, position: [f64; 3], radius: f64, mut filter: F) -> Vec<Entity> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut taken = Vec::new();
+        let near = |e: &Entity| {
+            let p = e.body.position();
+            let size = e.kind.size();
+            let center = [p[0] + size[0] / 2., p[1] + size[1] / 2., p[2] + size[2] / 2.];
+            (0..3).map(|i| (center[i] - position[i]).powi(2)).sum::<f64>() <= radius * radius
+        };
+        for entities in inner.by_chunk.values_mut() {
+            let mut i = 0;
+            while i < entities.len() {
+                if near(&entities[i].1) && filter(&entities[i].1) {
+                    taken.push(entities.swap_remove(i).1);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        taken
+    }
+
+    /// unknown are saved entities which could not be restored
+    pub fn load_chunk<I: Iterator<Item = Entity>>(&self, chunk: ChunkPos, entities: I, unknown: Vec<SavedEntity>) {
+        let mut inner = self.inner.lock().unwrap();
+        if !unknown.is_empty() {
+            inner.unknown.insert(chunk, unknown);
+        }
+        let mut loaded = Vec::new();
+        for entity in entities {
+            loaded.push((EntityId(inner.next_id), entity));
+            inner.next_id += 1;
+        }
+        if inner.by_chunk.insert(chunk, loaded).is_some() {
+            panic!("{:?} already loaded", chunk);
+        }
+    }
+
+    /// returns the serialized entities of the chunk
+    pub fn unload_chunk(&self, chunk: ChunkPos) -> Vec<SavedEntity> {
+        let _ticking = self.ticking.lock().unwrap();
+        let mut inner = self.inner.lock().unwrap();
+        let entities = inner.by_chunk.remove(&chunk).expect("chunk is loaded");
+        let mut saved: Vec<SavedEntity> = entities.iter().map(|e| SavedEntity::new(&e.1)).collect();
+        saved.extend(inner.unknown.remove(&chunk).unwrap_or_default());
+        saved
+    }
+
+    /// the serialized entities of a chunk which is saved while staying loaded
+    pub fn chunk_entities(&self, chunk: ChunkPos) -> Vec<SavedEntity> {
+        let _ticking = self.ticking.lock().unwrap();
+        let inner = self.inner.lock().unwrap();
+        let mut saved: Vec<SavedEntity> = inner
+            .by_chunk
+            .get(&chunk)
+            .map_or(Vec::new(), |entities| entities.iter().map(|e| SavedEntity::new(&e.1)).collect());
+        if let Some(unknown) = inner.unknown.get(&chunk) {
+            saved.extend(unknown.iter().cloned());
+        }
+        saved
+    }
+
+    pub fn views(&self) -> Vec<EntityView> {
+        self.views.lock().unwrap().clone()
+    }
+
+    pub fn run(&self, world: &World) {
+        //the map is not locked while entities are ticked, so they can spawn new entities
+        let _ticking = self.ticking.lock().unwrap();
+        let ticking = {
+            let mut inner = self.inner.lock().unwrap();
+            let empty = inner.by_chunk.keys().map(|&c| (c, Vec::new())).collect();
+            mem::replace(&mut inner.by_chunk, empty)
+        };
+        let mut ticked: Vec<(ChunkPos, EntityId, Entity)> = Vec::new();
+        for (chunk, entities) in ticking {
+            for (id, mut entity) in entities {
+                if !entity.kind.tick(world, id, &mut entity.body) {
+                    continue;
+                }
+                entity.body.tick(Some(world), true);
+                ticked.push((chunk, id, entity));
+            }
+        }
+        let mut views = Vec::with_capacity(ticked.len());
+        let mut inner = self.inner.lock().unwrap();
+        for (chunk, id, entity) in ticked {
+            let feet = entity.body.feet_block();
+            let light = world.natural_light(feet).map_or(0, |l| l.0).max(world.artificial_light(feet).map_or(0, |l| l.0));
+            views.push(EntityView {
+                id,
+                position: entity.body.position(),
+                size: entity.kind.size(),
+                draw: entity.kind.draw(),
+                light,
+            });
+            let chunk = if inner.by_chunk.contains_key(&entity.chunk()) { entity.chunk() } else { chunk };
+            inner.by_chunk.get_mut(&chunk).expect("chunk is loaded").push((id, entity));
+        }
+        *self.views.lock().unwrap() = views;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::iter;
+
+    #[test]
+    fn unknown_entities_are_kept() {
+        let map = EntityMap::new();
+        let chunk = ChunkPos([0, 1, 0]);
+        let saved = SavedEntity {
+            type_name: "removed".into(),
+            position: [1., 17., 1.],
+            velocity: [0.; 3],
+            data: vec![1, 2],
+        };
+        map.load_chunk(chunk, iter::empty(), vec![saved.clone()]);
+        assert_eq!(map.chunk_entities(chunk), vec![saved.clone()]);
+        assert_eq!(map.unload_chunk(chunk), vec![saved]);
+        assert!(map.chunk_entities(chunk).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use block::{BlockId, BlockState, BlockRegistry, BlockBehavior, Fluid, FluidLevel};
+use geometry::Direction;
+use world::{World, BlockPos};
+
+const HORIZONTAL: [Direction; 4] = [Direction::PosX, Direction::NegX, Direction::PosZ, Direction::NegZ];
+
+/// the behavior of blocks with fluid properties, updates them in their scheduled ticks
+pub struct FluidBehavior;
+
+impl BlockBehavior for FluidBehavior {
+    fn on_scheduled_tick(&self, world: &World, pos: BlockPos, _: BlockState) {
+        update(world, pos);
+    }
+}
+
+/// fluid blocks are updated by a scheduled tick some ticks after a change at or next to them
+pub fn schedule(world: &World, pos: BlockPos, state: BlockState) {
+    if let Some(ref fluid) = world.game_data().blocks().properties(state.id).fluid {
+        world.schedule_tick(pos, u64::from(fluid.tick_delay.max(1))).ok();
+    }
+}
+
+pub fn update(world: &World, pos: BlockPos) {
+    let blocks = world.game_data().blocks();
+    let state = match world.get_block_state(pos) {
+        Some(state) => state,
+        None => return,
+    };
+    let fluid = match blocks.properties(state.id).fluid {
+        Some(ref fluid) => fluid,
+        None => return,
+    };
+    let level = fluid.level(state.meta);
+    let new_level = if level == FluidLevel::Source {
+        Some(level)
+    } else {
+        flowing_level(world, pos, state.id, fluid)
+    };
+    if new_level != Some(level) {
+        let new_state = match new_level {
+            Some(l) => BlockState::with_meta(state.id, fluid.meta(l)),
+            None => BlockState::new(BlockId::empty()),
+        };
+        if world.set_block_state(pos, new_state).is_err() {
+            return;
+        }
+    }
+    if let Some(level) = new_level {
+        spread(world, pos, state.id, fluid, level);
+    }
+}
+
+/// the level a non source block should have given its neighbours, None if it should dry up
+fn flowing_level(world: &World, pos: BlockPos, id: BlockId, fluid: &Fluid) -> Option<FluidLevel> {
+    let level_at = |p: BlockPos| {
+        world
+            .get_block_state(p)
+            .and_then(|s| if s.id == id { Some(fluid.level(s.meta)) } else { None })
+    };
+    if level_at(pos.facing(Direction::PosY)).is_some() {
+        return Some(FluidLevel::Falling);
+    }
+    let mut sources = 0;
+    let mut min_level = None;
+    for d in &HORIZONTAL {
+        let level = match level_at(pos.facing(*d)) {
+            Some(FluidLevel::Source) => {
+                sources += 1;
+                1
+            }
+            Some(FluidLevel::Falling) => 1,
+            Some(FluidLevel::Flowing(l)) => l + 1,
+            None => continue,
+        };
+        min_level = Some(min_level.map_or(level, |m: u32| m.min(level)));
+    }
+    if fluid.renewable && sources >= 2 {
+        let below = pos.facing(Direction::NegY);
+        let supported = level_at(below) == Some(FluidLevel::Source) ||
+            world.get_block(below).map_or(false, |b| world.game_data().blocks().properties(b).collides);
+        if supported {
+            return Some(FluidLevel::Source);
+        }
+    }
+    min_level.and_then(|l| if l <= fluid.spread { Some(FluidLevel::Flowing(l)) } else { None })
+}
+
+/// fluid falls down if possible, otherwise it flows sideways
+fn spread(world: &World, pos: BlockPos, id: BlockId, fluid: &Fluid, level: FluidLevel) {
+    let blocks = world.game_data().blocks();
+    let below = pos.facing(Direction::NegY);
+    match world.get_block_state(below) {
+        Some(s) if can_replace(blocks, s) => {
+            world.set_block_state(below, BlockState::with_meta(id, fluid.meta(FluidLevel::Falling))).ok();
+            return;
+        }
+        Some(s) if s.id == id && fluid.level(s.meta) != FluidLevel::Source => return,
+        Some(_) => {}
+        None => return,
+    }
+    let next = match level {
+        FluidLevel::Source | FluidLevel::Falling => 1,
+        FluidLevel::Flowing(l) => l + 1,
+    };
+    if next > fluid.spread {
+        return;
+    }
+    for d in &HORIZONTAL {
+        let p = pos.facing(*d);
+        if world.get_block_state(p).map_or(false, |s| can_replace(blocks, s)) {
+            world.set_block_state(p, BlockState::with_meta(id, fluid.meta(FluidLevel::Flowing(next)))).ok();
+        }
+    }
+}
+
+/// fluids flow into non colliding blocks like air and plants, but not into other fluids
+fn can_replace(blocks: &BlockRegistry, state: BlockState) -> bool {
+    let properties = blocks.properties(state.id);
+    !properties.collides && properties.fluid.is_none()
+}
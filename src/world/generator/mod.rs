@@ -5,16 +5,23 @@ pub mod overworld;
 pub mod noise;
 pub mod structure;
 
+/// controllers created by structures for the blocks they placed
+/// generators cannot access the GameData, so they are restored by the BlockControllerRegistry when the chunk is inserted
+pub type GeneratedControllers = Vec<SavedBlockController>;
+
 pub trait Generator
 where
     Self: Send + Sync,
 {
     fn biome_at(&self, x: i32, z: i32) -> BiomeId;
     fn biome_map(&self, pos: ChunkPos) -> [[BiomeId; CHUNK_SIZE]; CHUNK_SIZE];
-    fn gen_chunk(&self, pos: ChunkPos) -> Box<ChunkArray<AtomicBlockId>>;
+    fn gen_chunk(&self, pos: ChunkPos) -> (Box<ChunkArray<AtomicBlockId>>, GeneratedControllers);
     fn reseed(&mut self, &WorldRngSeeder);
 }
 
 pub trait TerrainInformation {
+    /// y coordinate of the topmost ground block
     fn surface_y(&self, x: i32, z: i32) -> i32;
+    fn biome_at(&self, x: i32, z: i32) -> BiomeId;
+    fn sea_level(&self) -> Option<i32>;
 }
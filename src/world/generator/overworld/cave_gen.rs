@@ -0,0 +1,80 @@
+use noise::{Perlin, NoiseFn};
+
+/// carves caves out of the ground of a biome
+/// tunnels follow the intersection of the zero surfaces of two 3d noise functions,
+/// caverns are placed where a third noise exceeds a threshold
+/// all noise is sampled at world coordinates, so chunks can be generated in any order
+pub struct CaveGen {
+    tunnel_wavelength: f32,
+    tunnel_width: f32,
+    cavern_wavelength: f32,
+    cavern_threshold: f32,
+    min_depth: i32,
+}
+
+/// noise functions shared by all cave generators of a world
+pub struct CaveNoise([Perlin; 3]);
+
+impl CaveNoise {
+    pub fn new<I: Iterator<Item = Perlin>>(mut noises: I) -> Self {
+        CaveNoise([
+            noises.next().unwrap(),
+            noises.next().unwrap(),
+            noises.next().unwrap(),
+        ])
+    }
+}
+
+impl CaveGen {
+    /// creates a generator which carves nothing
+    pub fn new() -> Self {
+        CaveGen {
+            tunnel_wavelength: 1.,
+            tunnel_width: 0.,
+            cavern_wavelength: 1.,
+            cavern_threshold: 1.,
+            min_depth: 0,
+        }
+    }
+
+    /// width is relative to the noise amplitude, values around 0.05 give narrow tunnels
+    pub fn tunnels(mut self, wavelength: f32, width: f32) -> Self {
+        self.tunnel_wavelength = wavelength;
+        self.tunnel_width = width;
+        self
+    }
+
+    /// threshold is in 0..1, lower values produce more and larger caverns
+    pub fn caverns(mut self, wavelength: f32, threshold: f32) -> Self {
+        self.cavern_wavelength = wavelength;
+        self.cavern_threshold = threshold;
+        self
+    }
+
+    /// no blocks within depth blocks below the surface are carved
+    pub fn min_depth(mut self, depth: i32) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    pub fn is_cave(&self, noise: &CaveNoise, pos: [i32; 3], surface: i32) -> bool {
+        if pos[1] > surface - self.min_depth {
+            return false;
+        }
+        let sample = |perlin: &Perlin, wavelength: f32| {
+            let scale = f64::from(wavelength.recip());
+            perlin.get([
+                f64::from(pos[0]) * scale,
+                f64::from(pos[1]) * scale,
+                f64::from(pos[2]) * scale,
+            ]) as f32
+        };
+        if self.tunnel_width > 0. &&
+            sample(&noise.0[0], self.tunnel_wavelength).abs() < self.tunnel_width &&
+            sample(&noise.0[1], self.tunnel_wavelength).abs() < self.tunnel_width
+        {
+            return true;
+        }
+        self.cavern_threshold < 1. && sample(&noise.0[2], self.cavern_wavelength) > self.cavern_threshold
+    }
+}
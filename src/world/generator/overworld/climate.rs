@@ -0,0 +1,73 @@
+use std::cmp::Ordering;
+use noise::{Perlin, NoiseFn};
+
+const CLIMATE_WAVELENGTH: f64 = 1024.;
+const DETAIL_WAVELENGTH: f64 = 64.;
+const DETAIL_AMPLITUDE: f64 = 0.05;
+
+/// the temperatures and humidities a biome is generated at, both range from 0 to 1
+#[derive(Copy, Clone)]
+pub struct Climate {
+    temperature: (f32, f32),
+    humidity: (f32, f32),
+}
+
+impl Climate {
+    pub fn new(temperature: (f32, f32), humidity: (f32, f32)) -> Self {
+        Climate { temperature, humidity }
+    }
+
+    /// first element is the distance to the range, zero if inside
+    /// second element is the distance to the center of the range
+    fn fit(&self, temperature: f32, humidity: f32) -> (f32, f32) {
+        fn outside(v: f32, range: (f32, f32)) -> f32 {
+            (range.0 - v).max(v - range.1).max(0.)
+        }
+        fn center(v: f32, range: (f32, f32)) -> f32 {
+            v - (range.0 + range.1) / 2.
+        }
+        let ot = outside(temperature, self.temperature);
+        let oh = outside(humidity, self.humidity);
+        let ct = center(temperature, self.temperature);
+        let ch = center(humidity, self.humidity);
+        ((ot * ot + oh * oh).sqrt(), (ct * ct + ch * ch).sqrt())
+    }
+}
+
+/// returns the index of the climate fitting best, panics if climates is empty
+pub fn best_fit(climates: &[Climate], temperature: f32, humidity: f32) -> usize {
+    climates
+        .iter()
+        .map(|c| c.fit(temperature, humidity))
+        .enumerate()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        .expect("no biomes")
+        .0
+}
+
+/// large scale temperature and humidity noise
+/// a small amount of detail noise makes biome borders less smooth
+pub struct ClimateNoise([Perlin; 4]);
+
+impl ClimateNoise {
+    pub fn new<I: Iterator<Item = Perlin>>(mut noises: I) -> Self {
+        ClimateNoise([
+            noises.next().unwrap(),
+            noises.next().unwrap(),
+            noises.next().unwrap(),
+            noises.next().unwrap(),
+        ])
+    }
+
+    /// returns temperature and humidity at a position
+    pub fn sample(&self, x: i32, z: i32) -> (f32, f32) {
+        let sample = |base: &Perlin, detail: &Perlin| {
+            let (x, z) = (f64::from(x), f64::from(z));
+            let v = base.get([x / CLIMATE_WAVELENGTH, z / CLIMATE_WAVELENGTH]) +
+                detail.get([x / DETAIL_WAVELENGTH, z / DETAIL_WAVELENGTH]) * DETAIL_AMPLITUDE;
+            //perlin noise is centered on zero, move it to the range of climates and clamp the rare outliers
+            (0.5 + v).max(0.).min(1.) as f32
+        };
+        (sample(&self.0[0], &self.0[1]), sample(&self.0[2], &self.0[3]))
+    }
+}
@@ -4,8 +4,14 @@ use world::random::*;
 use block::BlockId;
 use std::cmp::max;
 
+type Layer = (BlockId, Perlin, f32, f32);
+
+/// layers of blocks below the surface
+/// columns whose surface is close to sea level use the beach layers instead, if there are any
 pub struct GroundGen {
-    layers: Vec<(BlockId, Perlin, f32, f32)>,
+    layers: Vec<Layer>,
+    beach_layers: Vec<Layer>,
+    beach_height: i32,
     noise_iter: NoiseIterator,
 }
 
@@ -14,6 +20,8 @@ impl GroundGen {
     pub fn new() -> Self {
         GroundGen {
             layers: vec![],
+            beach_layers: vec![],
+            beach_height: 0,
             noise_iter: WorldRngSeeder::new(0).noises(),
         }
     }
@@ -21,6 +29,9 @@ impl GroundGen {
         for (&mut (_, ref mut perlin, _, _), noise) in self.layers.iter_mut().zip(r.noises()) {
             *perlin = noise
         }
+        for (&mut (_, ref mut perlin, _, _), noise) in self.beach_layers.iter_mut().zip(r.push_num(1).noises()) {
+            *perlin = noise
+        }
     }
     pub fn push_layer(mut self, block: BlockId, min_thickness: f32, max_depth: f32) -> Self {
         self.layers.push((
@@ -31,14 +42,33 @@ impl GroundGen {
         ));
         self
     }
+    /// columns whose surface is at most height blocks above sea level are beaches
+    pub fn beach_height(mut self, height: i32) -> Self {
+        self.beach_height = height;
+        self
+    }
+    pub fn push_beach_layer(mut self, block: BlockId, min_thickness: f32, max_depth: f32) -> Self {
+        self.beach_layers.push((
+            block,
+            self.noise_iter.next().unwrap(),
+            min_thickness,
+            max_depth,
+        ));
+        self
+    }
+    /// height_above_sea is None if there is no sea level
     pub fn gen_column<F: FnMut(usize, BlockId)>(
         &self,
         gen_depth: i32,
+        height_above_sea: Option<i32>,
         set_block: &mut F,
         x: i32,
         z: i32,
     ) -> usize {
-        let mut layer_iter = self.layers.iter().skip_while(|&&(_, _, _, max)| {
+        let beach = !self.beach_layers.is_empty() &&
+            height_above_sea.map_or(false, |h| h <= self.beach_height);
+        let layers = if beach { &self.beach_layers } else { &self.layers };
+        let mut layer_iter = layers.iter().skip_while(|&&(_, _, _, max)| {
             max - 0.01 <= gen_depth as f32
         });
         let mut i = (-gen_depth).max(0) as usize;
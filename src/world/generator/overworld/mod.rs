@@ -1,9 +1,8 @@
 use noise::Perlin;
 use chashmap::*;
-use rand::Rng;
 use num::Integer;
 use world::generator::structure::*;
-use world::generator::Generator;
+use world::generator::{Generator, GeneratedControllers};
 use world::*;
 use block::{AtomicBlockId, BlockId};
 use world::biome::*;
@@ -11,8 +10,17 @@ use world::generator::noise::NoiseParameters;
 use world::generator::TerrainInformation;
 
 pub use self::ground_layer_gen::GroundGen;
+pub use self::cave_gen::CaveGen;
+pub use self::ore_gen::OreGen;
+pub use self::climate::Climate;
+
+use self::cave_gen::CaveNoise;
+use self::climate::ClimateNoise;
 
 mod ground_layer_gen;
+mod cave_gen;
+mod ore_gen;
+mod climate;
 
 const BIOME_GEN_CHUNKS: usize = 8;
 const BIOME_GEN_SIZE: usize = BIOME_GEN_CHUNKS * CHUNK_SIZE;
@@ -20,12 +28,21 @@ const BIOME_GEN_SIZE: usize = BIOME_GEN_CHUNKS * CHUNK_SIZE;
 type BiomeMap = [[usize; BIOME_GEN_SIZE + 1]; BIOME_GEN_SIZE + 1];
 type HeightMap = [[i32; CHUNK_SIZE]; CHUNK_SIZE];
 
+/// terrain heights are averaged over the biomes at these distances around a position
+const BLEND_DISTANCES: [i32; 5] = [1, 2, 3, 4, 8];
+
 pub struct OverworldGenerator {
     structures: CombinedStructureGenerator,
     biomes: Vec<BiomeId>,
+    climates: Vec<Climate>,
+    climate_noise: ClimateNoise,
     terrain_parameters: Vec<NoiseParameters>,
     terrain_bases: Vec<i32>,
     ground_layers: Vec<GroundGen>,
+    caves: Vec<Option<CaveGen>>,
+    cave_noise: CaveNoise,
+    ores: Vec<OreGen>,
+    sea: Option<(i32, BlockId)>,
     biome_maps: CHashMap<[i32; 2], Box<BiomeMap>>,
     rand: WorldRngSeeder,
     noise_gen: Vec<Perlin>,
@@ -41,9 +58,15 @@ impl OverworldGenerator {
         OverworldGenerator {
             structures: CombinedStructureGenerator::new(structures, rand),
             biomes: vec![],
+            climates: vec![],
+            climate_noise: ClimateNoise::new(rand.push_num(8).noises()),
             terrain_parameters: vec![],
             terrain_bases: vec![],
             ground_layers: vec![],
+            caves: vec![],
+            cave_noise: CaveNoise::new(rand.push_num(6).noises()),
+            ores: vec![],
+            sea: None,
             biome_maps: CHashMap::new(),
             rand: rand,
             noise_gen: rand.noises().take(16).collect(),
@@ -57,12 +80,31 @@ impl OverworldGenerator {
         terrain: NoiseParameters,
         terrain_base: i32,
         mut layers: GroundGen,
+        climate: Climate,
     ) {
         self.biomes.push(b);
+        self.climates.push(climate);
         self.terrain_parameters.push(terrain);
         self.terrain_bases.push(terrain_base);
         layers.reseed(&self.rand);
         self.ground_layers.push(layers);
+        self.caves.push(None);
+    }
+
+    /// panics if the biome has not been added
+    pub fn set_caves(&mut self, b: BiomeId, caves: CaveGen) {
+        let index = self.biomes.iter().position(|&x| x == b).expect("biome not in generator");
+        self.caves[index] = Some(caves);
+    }
+
+    /// open air at or below level is filled with water
+    pub fn set_sea_level(&mut self, level: i32, water: BlockId) {
+        self.sea = Some((level, water));
+    }
+
+    /// ores are generated in the order they are added, after caves have been carved
+    pub fn add_ore(&mut self, ore: OreGen) {
+        self.ores.push(ore);
     }
 
     fn extract_chunk_biomes(
@@ -81,34 +123,14 @@ impl OverworldGenerator {
     }
 
     fn gen_biome_map(&self, x: i32, z: i32) -> Box<BiomeMap> {
-        let mut map = Box::new(
-            [[usize::max_value(); BIOME_GEN_SIZE + 1]; BIOME_GEN_SIZE + 1],
-        );
-        map[0][0] = self.corner_biome(x, z);
-        map[0][BIOME_GEN_SIZE] = self.corner_biome(x, z + 1);
-        map[BIOME_GEN_SIZE][0] = self.corner_biome(x + 1, z);
-        map[BIOME_GEN_SIZE][BIOME_GEN_SIZE] = self.corner_biome(x + 1, z + 1);
-        spread_1d(0, BIOME_GEN_SIZE, &mut map, &mut self.rand.pushi(&[1, x, z]).rng(), &mut |map, i| &mut map[0][i]);
-        spread_1d(0, BIOME_GEN_SIZE, &mut map, &mut self.rand.pushi(&[2, x, z]).rng(), &mut |map, i| &mut map[i][0]);
-        spread_1d(0, BIOME_GEN_SIZE, &mut map, &mut self.rand.pushi(&[3, x + 1, z]).rng(), &mut |map, i| &mut map[BIOME_GEN_SIZE][i]);
-        spread_1d(0, BIOME_GEN_SIZE, &mut map, &mut self.rand.pushi(&[4, x, z + 1]).rng(), &mut |map, i| &mut map[i][BIOME_GEN_SIZE]);
-        for i in 0..BIOME_GEN_SIZE + 1 {
-            assert_ne!(map[0][i], usize::max_value());
-            assert_ne!(map[i][0], usize::max_value());
-            assert_ne!(map[BIOME_GEN_SIZE][i], usize::max_value());
-            assert_ne!(map[i][BIOME_GEN_SIZE], usize::max_value());
+        let bgs = BIOME_GEN_SIZE as i32;
+        let mut map = Box::new([[0; BIOME_GEN_SIZE + 1]; BIOME_GEN_SIZE + 1]);
+        for dx in 0..BIOME_GEN_SIZE + 1 {
+            for dz in 0..BIOME_GEN_SIZE + 1 {
+                let (temperature, humidity) = self.climate_noise.sample(x * bgs + dx as i32, z * bgs + dz as i32);
+                map[dx][dz] = climate::best_fit(&self.climates, temperature, humidity);
+            }
         }
-
-
-        spread_biomes(
-            &mut map,
-            &mut self.rand.rng(),
-            0,
-            0,
-            BIOME_GEN_SIZE,
-            false,
-            false,
-        );
         map
     }
 
@@ -128,15 +150,10 @@ impl OverworldGenerator {
         }
     }
 
-    fn corner_biome(&self, x: i32, z: i32) -> usize {
-        let mut gen = self.rand.push_num(5).pushi(&[x, z]).rng();
-        gen.gen_range(0, self.biomes.len())
-    }
-
     fn base_height_at(&self, x: i32, z: i32, reader: &mut BiomeReader) -> i32 {
         let mut r = 0;
         let mut count = 0;
-        for dist in &[1, 2, 3, 4, 8] {
+        for dist in &BLEND_DISTANCES {
             for dx in -1..2 {
                 for dz in -1..2 {
                     r += self.terrain_bases[reader.get(x + dx * dist, z + dz * dist)];
@@ -147,20 +164,29 @@ impl OverworldGenerator {
         r / count
     }
 
+    /// the noise of each biome near the position is weighted by how often it occurs around it,
+    /// so the terrain changes smoothly between biomes
     fn noise_height_at(&self, x: i32, z: i32, reader: &mut BiomeReader) -> i32 {
-        let biome = reader.get(x, z);
-        let biome_nx = reader.get(x + 1, z);
-        let biome_nz = reader.get(x, z + 1);
-        let height = if biome == biome_nx && biome == biome_nz {
-            self.terrain_parameters[biome].generate(x as f32, z as f32, self.noise_gen.iter())
-        } else {
-            [biome, biome_nx, biome_nz]
-                .iter()
-                .map(|b| {
-                    self.terrain_parameters[*b].generate(x as f32, z as f32, self.noise_gen.iter())
-                })
-                .sum::<f32>() / 3.
-        };
+        let mut weights: Vec<(usize, u32)> = Vec::new();
+        let mut count = 0;
+        for dist in &BLEND_DISTANCES {
+            for dx in -1..2 {
+                for dz in -1..2 {
+                    let biome = reader.get(x + dx * dist, z + dz * dist);
+                    match weights.iter().position(|w| w.0 == biome) {
+                        Some(i) => weights[i].1 += 1,
+                        None => weights.push((biome, 1)),
+                    }
+                    count += 1;
+                }
+            }
+        }
+        let height = weights
+            .iter()
+            .map(|&(biome, weight)| {
+                self.terrain_parameters[biome].generate(x as f32, z as f32, self.noise_gen.iter()) * weight as f32
+            })
+            .sum::<f32>() / count as f32;
         height.round() as i32
     }
 
@@ -193,6 +219,14 @@ impl TerrainInformation for OverworldGenerator {
         let mut reader = BiomeReader::new(self);
         self.base_height_at(x, z, &mut reader) + self.noise_height_at(x, z, &mut reader)
     }
+
+    fn biome_at(&self, x: i32, z: i32) -> BiomeId {
+        Generator::biome_at(self, x, z)
+    }
+
+    fn sea_level(&self) -> Option<i32> {
+        self.sea.map(|(level, _)| level)
+    }
 }
 
 impl Generator for OverworldGenerator {
@@ -212,7 +246,7 @@ impl Generator for OverworldGenerator {
         self.biomes[reader.get(x, z)]
     }
 
-    fn gen_chunk(&self, pos: ChunkPos) -> Box<ChunkArray<AtomicBlockId>> {
+    fn gen_chunk(&self, pos: ChunkPos) -> (Box<ChunkArray<AtomicBlockId>>, GeneratedControllers) {
         let mut biome_reader = BiomeReader::new(self);
         let hm = self.gen_height_map(pos[0], pos[2], &mut biome_reader);
         let mut chunk = Box::new(ChunkArray::<AtomicBlockId>::default());
@@ -226,6 +260,7 @@ impl Generator for OverworldGenerator {
                 let depth = hm[x][z] - (pos[1] * CHUNK_SIZE as i32 + end_index as i32);
                 let gen_depth = self.ground_layers[biome].gen_column(
                     depth,
+                    self.sea.map(|(level, _)| hm[x][z] - level),
                     &mut |d, block| {
                         chunk[[x, end_index - d, z]] = AtomicBlockId::new(block)
                     },
@@ -235,10 +270,40 @@ impl Generator for OverworldGenerator {
                 for i in 0..(CHUNK_SIZE - gen_depth) {
                     chunk[[x, i, z]] = AtomicBlockId::new(self.ground);
                 }
+                if let Some(ref caves) = self.caves[biome] {
+                    for y in 0..CHUNK_SIZE {
+                        let block_pos = [
+                            x as i32 + pos[0] * CHUNK_SIZE as i32,
+                            y as i32 + pos[1] * CHUNK_SIZE as i32,
+                            z as i32 + pos[2] * CHUNK_SIZE as i32,
+                        ];
+                        if caves.is_cave(&self.cave_noise, block_pos, hm[x][z]) {
+                            chunk[[x, y, z]] = AtomicBlockId::new(BlockId::empty());
+                        }
+                    }
+                }
+                if let Some((level, water)) = self.sea {
+                    for y in 0..CHUNK_SIZE {
+                        let block_y = y as i32 + pos[1] * CHUNK_SIZE as i32;
+                        if block_y > hm[x][z] && block_y <= level {
+                            chunk[[x, y, z]] = AtomicBlockId::new(water);
+                        }
+                    }
+                }
             }
         }
-        self.structures.generate_chunk(pos, &mut chunk, self);
-        chunk
+        for (i, ore) in self.ores.iter().enumerate() {
+            let mut rand = self.rand.push_num(7).pushi(&*pos).push_num(i as u32).rng();
+            ore.generate(pos, &mut chunk, &mut rand, &mut |x, z| {
+                self.biomes[biome_reader.get(
+                    x as i32 + pos[0] * CHUNK_SIZE as i32,
+                    z as i32 + pos[2] * CHUNK_SIZE as i32,
+                )]
+            });
+        }
+        let mut controllers = Vec::new();
+        self.structures.generate_chunk(pos, &mut chunk, &mut controllers, self);
+        (chunk, controllers)
     }
 
     fn reseed(&mut self, s: &WorldRngSeeder) {
@@ -247,57 +312,13 @@ impl Generator for OverworldGenerator {
             l.reseed(s);
         }
         self.noise_gen = s.noises().take(16).collect();
+        self.cave_noise = CaveNoise::new(s.push_num(6).noises());
+        self.climate_noise = ClimateNoise::new(s.push_num(8).noises());
         self.structures.reseed(s);
         self.biome_maps.clear();
     }
 }
 
-fn spread_1d<R, I>(min: usize, max: usize, map: &mut BiomeMap, rand: &mut R, index: &mut I)
-where
-    R: Rng,
-    I: for<'b> FnMut(&'b mut BiomeMap, usize) -> &'b mut usize,
-{
-    let center_biome = *index(map, if rand.gen() { min } else { max });
-    *index(map, (min + max) / 2) = center_biome;
-    if max - min > 2 {
-        spread_1d(min, (min + max) / 2, map, rand, index);
-        spread_1d((min + max) / 2, max, map, rand, index);
-    }
-}
-
-fn spread_biomes(
-    map: &mut BiomeMap,
-    rand: &mut WorldGenRng,
-    x: usize,
-    z: usize,
-    s: usize,
-    px: bool,
-    pz: bool,
-) {
-    assert_ne!(map[x][z], usize::max_value());
-    assert_ne!(map[x + s][z], usize::max_value());
-    assert_ne!(map[x][z + s], usize::max_value());
-    assert_ne!(map[x + s][z + s], usize::max_value());
-    let rnd_val: u8 = rand.gen();
-    let hs = s / 2;
-    let cx = x + hs;
-    let cz = z + hs;
-    map[cx][cz] =
-        map[if (rnd_val & 1) != 0 { x } else { x + s }][if (rnd_val & 2) != 0 { z } else { z + s }];
-    if px {
-        map[x + s][cz] = map[x + s][if (rnd_val & 4) != 0 { z } else { z + s }];
-    }
-    if pz {
-        map[cx][z + s] = map[if (rnd_val & 8) != 0 { x } else { x + s }][z + s];
-    }
-    if s > 2 {
-        spread_biomes(map, rand, x, z, hs, true, true);
-        spread_biomes(map, rand, cx, z, hs, px, true);
-        spread_biomes(map, rand, x, cz, hs, true, pz);
-        spread_biomes(map, rand, cx, cz, hs, px, pz);
-    }
-}
-
 struct BiomeReader<'a> {
     generator: &'a OverworldGenerator,
     guard: Option<ReadGuard<'a, [i32; 2], Box<BiomeMap>>>,
@@ -0,0 +1,83 @@
+use std::ops::Range;
+use rand::Rng;
+use world::{CHUNK_SIZE, ChunkArray, ChunkPos, WorldGenRng};
+use world::biome::BiomeId;
+use block::{AtomicBlockId, BlockId};
+
+/// scatters veins of a block through a host block
+/// veins are random walks starting inside the chunk and are clipped at its border
+pub struct OreGen {
+    block: BlockId,
+    host: BlockId,
+    vein_size: usize,
+    count: usize,
+    height: Range<i32>,
+    biomes: Option<Vec<BiomeId>>,
+}
+
+impl OreGen {
+    /// places one vein of eight blocks per chunk at any height by default
+    pub fn new(block: BlockId, host: BlockId) -> Self {
+        OreGen {
+            block,
+            host,
+            vein_size: 8,
+            count: 1,
+            height: i32::min_value()..i32::max_value(),
+            biomes: None,
+        }
+    }
+
+    pub fn vein_size(mut self, size: usize) -> Self {
+        self.vein_size = size;
+        self
+    }
+
+    /// number of veins per chunk
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// range of world y coordinates veins may start at
+    pub fn height(mut self, height: Range<i32>) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// only start veins in the given biomes
+    pub fn biomes(mut self, biomes: Vec<BiomeId>) -> Self {
+        self.biomes = Some(biomes);
+        self
+    }
+
+    pub fn generate<B>(&self, pos: ChunkPos, chunk: &mut ChunkArray<AtomicBlockId>, rand: &mut WorldGenRng, biome_at: &mut B)
+        where B: FnMut(usize, usize) -> BiomeId
+    {
+        let cs = CHUNK_SIZE as i32;
+        for _ in 0..self.count {
+            let mut p = [
+                rand.gen_range(0, cs),
+                rand.gen_range(0, cs),
+                rand.gen_range(0, cs),
+            ];
+            if !self.height.contains(&(pos[1] * cs + p[1])) {
+                continue;
+            }
+            if let Some(ref biomes) = self.biomes {
+                if !biomes.contains(&biome_at(p[0] as usize, p[2] as usize)) {
+                    continue;
+                }
+            }
+            for _ in 0..self.vein_size {
+                if p.iter().all(|&x| x >= 0 && x < cs) {
+                    let index = [p[0] as usize, p[1] as usize, p[2] as usize];
+                    if chunk[index].load() == self.host {
+                        chunk[index] = AtomicBlockId::new(self.block);
+                    }
+                }
+                p[rand.gen_range(0, 3)] += if rand.gen() { 1 } else { -1 };
+            }
+        }
+    }
+}
@@ -3,9 +3,9 @@ use std::cmp;
 use vecmath::*;
 use chashmap::*;
 use num::Integer;
-use world::{CHUNK_SIZE, ChunkArray, ChunkPos, BlockPos, WorldRngSeeder};
+use world::{CHUNK_SIZE, ChunkArray, ChunkPos, BlockPos, WorldRngSeeder, SavedBlockController};
 use block::{AtomicBlockId, BlockId};
-use world::generator::TerrainInformation;
+use world::generator::{TerrainInformation, GeneratedControllers};
 
 pub trait Structure
 where
@@ -47,6 +47,8 @@ where
 
 pub struct GeneratingChunk<'a> {
     chunk: &'a mut ChunkArray<AtomicBlockId>,
+    controllers: &'a mut GeneratedControllers,
+    chunk_pos: ChunkPos,
     struct_pos: [i32; 3],
 }
 
@@ -72,7 +74,26 @@ impl<'a> GeneratingChunk<'a> {
             false
         }
     }
-    #[allow(dead_code)]
+    /// the controller is deserialized from data and inserted with the chunk, returns false if pos is not in the chunk
+    pub fn add_block_controller(&mut self, pos: [i32; 3], type_name: &str, data: Vec<u8>) -> bool {
+        if let Some(p) = self.pos_in_chunk(pos) {
+            let cs = CHUNK_SIZE as i32;
+            let block_pos = BlockPos([
+                self.chunk_pos[0] * cs + p[0] as i32,
+                self.chunk_pos[1] * cs + p[1] as i32,
+                self.chunk_pos[2] * cs + p[2] as i32,
+            ]);
+            self.controllers.retain(|c| c.pos != block_pos);
+            self.controllers.push(SavedBlockController {
+                pos: block_pos,
+                type_name: type_name.into(),
+                data,
+            });
+            true
+        } else {
+            false
+        }
+    }
     pub fn get_block(&mut self, pos: [i32; 3]) -> Option<BlockId> {
         if let Some(pos) = self.pos_in_chunk(pos) {
             Some(self.chunk[pos].load())
@@ -126,6 +147,7 @@ impl CombinedStructureGenerator {
         &self,
         pos: ChunkPos,
         chunk: &mut ChunkArray<AtomicBlockId>,
+        controllers: &mut GeneratedControllers,
         terrain: &TerrainInformation,
     ) {
         let cs = CHUNK_SIZE as i32;
@@ -143,6 +165,8 @@ impl CombinedStructureGenerator {
                             let rel_struct_pos = vec3_sub((s.1).0, vec3_scale(pos.0, cs));
                             let mut gen_chunk = GeneratingChunk {
                                 chunk: chunk,
+                                controllers: controllers,
+                                chunk_pos: pos,
                                 struct_pos: rel_struct_pos,
                             };
                             s.0.generate(&mut gen_chunk, &rand, terrain);
@@ -182,3 +206,34 @@ impl CombinedStructureGenerator {
         ret
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_controllers_are_added_in_chunk() {
+        let mut blocks = ChunkArray::<AtomicBlockId>::default();
+        let mut controllers = Vec::new();
+        {
+            let mut chunk = GeneratingChunk {
+                chunk: &mut blocks,
+                controllers: &mut controllers,
+                chunk_pos: ChunkPos([1, 0, -1]),
+                struct_pos: [2, 3, 4],
+            };
+            assert!(chunk.add_block_controller([0, 1, 0], "a", vec![1]));
+            assert!(chunk.add_block_controller([0, 1, 0], "b", vec![2]));
+            assert!(!chunk.add_block_controller([-3, 0, 0], "c", vec![3]));
+        }
+        let cs = CHUNK_SIZE as i32;
+        assert_eq!(
+            controllers,
+            vec![SavedBlockController {
+                pos: BlockPos([cs + 2, 4, -cs + 4]),
+                type_name: "b".into(),
+                data: vec![2],
+            }]
+        );
+    }
+}
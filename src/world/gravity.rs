@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::Mutex;
+use block::{BlockId, BlockState, BlockRegistry, FluidLevel};
+use geometry::Direction;
+use world::{World, BlockPos, ChunkPos};
+
+/// blocks falling further are stopped
+const MAX_FALL_DISTANCE: i32 = 1024;
+
+/// blocks which are affected by gravity drop to the first colliding block below them
+/// blocks are checked after a change at or next to them
+/// blocks falling into a chunk which is not loaded wait until it is loaded
+/// waiting blocks are saved with their chunk
+#[derive(Default)]
+pub struct GravityUpdates {
+    pending: Mutex<HashSet<BlockPos>>,
+    waiting: Mutex<HashMap<ChunkPos, Vec<BlockPos>>>,
+}
+
+impl GravityUpdates {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// queues the block to be checked if it is affected by gravity
+    pub fn check(&self, blocks: &BlockRegistry, pos: BlockPos, state: BlockState) {
+        if blocks.properties(state.id).falls {
+            self.pending.lock().unwrap().insert(pos);
+        }
+    }
+
+    /// blocks waiting for the chunk and the saved waiting blocks of the chunk are checked again
+    pub fn load_chunk(&self, chunk: ChunkPos, saved: Vec<BlockPos>) {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(waiting) = self.waiting.lock().unwrap().remove(&chunk) {
+            pending.extend(waiting);
+        }
+        pending.extend(saved);
+    }
+
+    /// removes and returns the waiting blocks in a chunk which is unloaded
+    pub fn unload_chunk(&self, chunk: ChunkPos) -> Vec<BlockPos> {
+        let mut waiting = self.waiting.lock().unwrap();
+        let mut removed = Vec::new();
+        for positions in waiting.values_mut() {
+            let (in_chunk, rest): (Vec<BlockPos>, Vec<BlockPos>) =
+                positions.drain(..).partition(|p| p.pos_in_chunk().0 == chunk);
+            *positions = rest;
+            removed.extend(in_chunk);
+        }
+        waiting.retain(|_, positions| !positions.is_empty());
+        removed
+    }
+
+    /// the waiting blocks in a chunk which is saved while staying loaded
+    pub fn chunk_waiting(&self, chunk: ChunkPos) -> Vec<BlockPos> {
+        self.waiting
+            .lock()
+            .unwrap()
+            .values()
+            .flat_map(|positions| positions.iter().cloned())
+            .filter(|p| p.pos_in_chunk().0 == chunk)
+            .collect()
+    }
+
+    pub fn run(&self, world: &World) {
+        let pending = mem::replace(&mut *self.pending.lock().unwrap(), HashSet::new());
+        for pos in pending {
+            if let Some(unloaded) = fall(world, pos) {
+                self.waiting.lock().unwrap().entry(unloaded).or_insert_with(Vec::new).push(pos);
+            }
+        }
+    }
+}
+
+/// returns the chunk the block would fall into if it is not loaded, the block does not move in that case
+fn fall(world: &World, pos: BlockPos) -> Option<ChunkPos> {
+    let blocks = world.game_data().blocks();
+    let state = world.get_block_state(pos)?;
+    if !blocks.properties(state.id).falls {
+        return None;
+    }
+    //blocks fall through non colliding blocks like air, plants and fluids, replacing them
+    let mut target = pos;
+    for _ in 0..MAX_FALL_DISTANCE {
+        let below = target.facing(Direction::NegY);
+        match world.get_block(below) {
+            Some(b) if !blocks.properties(b).collides => target = below,
+            Some(_) => break,
+            None => return Some(below.pos_in_chunk().0),
+        }
+    }
+    if target == pos {
+        return None;
+    }
+    let replaced = world.get_block_state(target)?;
+    //fluid sources are moved to where the block was instead of being destroyed
+    let is_source = blocks
+        .properties(replaced.id)
+        .fluid
+        .as_ref()
+        .map_or(false, |f| f.level(replaced.meta) == FluidLevel::Source);
+    let left = if is_source { replaced } else { BlockState::new(BlockId::empty()) };
+    if world.set_block_state(target, state).is_ok() {
+        world.set_block_state(pos, left).ok();
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn waiting_blocks_are_removed_with_their_chunk() {
+        let gravity = GravityUpdates::new();
+        let below = ChunkPos([0, -1, 0]);
+        let a = BlockPos([1, 0, 1]);
+        let b = BlockPos([40, 0, 1]);
+        gravity.waiting.lock().unwrap().insert(below, vec![a, b]);
+        assert_eq!(gravity.chunk_waiting(ChunkPos([0, 0, 0])), vec![a]);
+        assert_eq!(gravity.unload_chunk(ChunkPos([0, 0, 0])), vec![a]);
+        assert_eq!(gravity.unload_chunk(ChunkPos([1, 0, 0])), vec![b]);
+        assert!(gravity.waiting.lock().unwrap().is_empty());
+    }
+}
@@ -1,19 +1,30 @@
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex, atomic::AtomicBool};
+use std::sync::{Arc, Mutex};
 use threadpool::ThreadPool;
 use block::*;
 use super::*;
 use world::World;
+use world::save::WorldSave;
+use logging::root_logger;
 
 pub struct QueuedChunk {
     light_sources: Vec<(BlockPos, u8)>,
     pos: ChunkPos,
-    data: Box<ChunkArray<AtomicBlockId>>,
+    data: PaletteArray<BlockState>,
+    scheduled_ticks: Vec<PendingTick>,
+    falling_blocks: Vec<BlockPos>,
     block_controllers: Vec<(BlockPos, Arc<BlockController>)>,
+    /// saved controllers which could not be restored, they are saved again with the chunk
+    unknown_controllers: Vec<SavedBlockController>,
+    entities: Vec<Entity>,
+    /// saved entities which could not be restored, they are saved again with the chunk
+    unknown_entities: Vec<SavedEntity>,
 }
 
+type Shared = (GameData, Mutex<InsertBuffer>, Arc<WorldSave>);
+
 pub struct Inserter {
-    shared: Arc<(GameData, Mutex<InsertBuffer>)>,
+    shared: Arc<Shared>,
     threads: Mutex<ThreadPool>,
 }
 
@@ -23,7 +34,7 @@ struct InsertBuffer {
 }
 
 impl Inserter {
-    pub fn new(gen: GameData) -> Self {
+    pub fn new(gen: GameData, save: Arc<WorldSave>) -> Self {
         Inserter {
             shared: Arc::new((
                 gen,
@@ -31,6 +42,7 @@ impl Inserter {
                     chunks: VecDeque::new(),
                     pending: Vec::new(),
                 }),
+                save,
             )),
             threads: Mutex::new(ThreadPool::with_name("chunk generator".into(), 3)),
         }
@@ -87,23 +99,58 @@ impl Inserter {
     }
 
     fn insert_chunk(world: &World, queued_chunk: QueuedChunk) {
-        let chunk = Arc::new(Chunk {
-            natural_light: Default::default(),
-            data: *queued_chunk.data,
-            artificial_light: Default::default(),
-            is_in_update_queue: AtomicBool::new(false),
-        });
+        let chunk = Arc::new(Chunk::new(queued_chunk.data));
         world.chunks.insert_chunk(queued_chunk.pos, chunk, &queued_chunk.light_sources);
-        world.block_controllers.load_chunk(queued_chunk.pos, queued_chunk.block_controllers.into_iter());
+        world.scheduled_ticks.load_chunk(&queued_chunk.scheduled_ticks, world.time.current_tick());
+        world.block_controllers.load_chunk(
+            queued_chunk.pos,
+            queued_chunk.block_controllers.into_iter(),
+            queued_chunk.unknown_controllers,
+        );
+        world.entities.load_chunk(queued_chunk.pos, queued_chunk.entities.into_iter(), queued_chunk.unknown_entities);
+        world.gravity.load_chunk(queued_chunk.pos, queued_chunk.falling_blocks);
     }
 
-    fn generate_chunk(shared: Arc<(GameData, Mutex<InsertBuffer>)>, pos: ChunkPos) {
-        let data = shared.0.generator().gen_chunk(pos);
+    /// load a chunk from disk, if it has not been saved it is generated
+    fn generate_chunk(shared: Arc<Shared>, pos: ChunkPos) {
+        let mut unknown_controllers = Vec::new();
+        let mut unknown_entities = Vec::new();
+        let mut falling_blocks = Vec::new();
+        let (data, scheduled_ticks, block_controllers, entities) = match shared.2.load_chunk(pos) {
+            Ok(Some(saved)) => {
+                let registry = shared.0.block_controllers();
+                let mut controllers = Vec::new();
+                for c in saved.block_controllers {
+                    match registry.deserialize(&shared.0, &c) {
+                        Some(controller) => controllers.push((c.pos, controller)),
+                        None => unknown_controllers.push(c),
+                    }
+                }
+                let mut entities = Vec::new();
+                for e in saved.entities {
+                    match shared.0.entities().deserialize(&shared.0, &e) {
+                        Some(entity) => entities.push(entity),
+                        None => unknown_entities.push(e),
+                    }
+                }
+                falling_blocks = saved.falling_blocks;
+                (saved.blocks, saved.scheduled_ticks, controllers, entities)
+            }
+            Ok(None) => {
+                let (data, controllers) = Self::run_generator(&shared.0, pos);
+                (data, Vec::new(), controllers, Vec::new())
+            }
+            Err(e) => {
+                error!(root_logger(), "cannot load chunk {:?}, regenerating: {}", pos, e);
+                let (data, controllers) = Self::run_generator(&shared.0, pos);
+                (data, Vec::new(), controllers, Vec::new())
+            }
+        };
         let mut sources = Vec::new();
         for x in 0..CHUNK_SIZE {
             for y in 0..CHUNK_SIZE {
                 for z in 0..CHUNK_SIZE {
-                    match *shared.0.blocks().light_type(data[[x, y, z]].load()) {
+                    match *shared.0.blocks().light_type(data.get([x, y, z]).id) {
                         LightType::Source(l) => {
                             sources.push((
                                 BlockPos(
@@ -116,14 +163,19 @@ impl Inserter {
                                 l,
                             ))
                         }
-                        LightType::Opaque | LightType::Transparent => {}
+                        LightType::Opaque | LightType::Transparent | LightType::Attenuating(_) => {}
                     }
                 }
             }
         }
         let insert = QueuedChunk {
-            block_controllers: Vec::new(),
+            block_controllers,
+            unknown_controllers,
+            entities,
+            unknown_entities,
             light_sources: sources,
+            scheduled_ticks,
+            falling_blocks,
             pos: pos,
             data: data,
         };
@@ -135,6 +187,16 @@ impl Inserter {
             }
         }
     }
+
+    fn run_generator(game_data: &GameData, pos: ChunkPos) -> (PaletteArray<BlockState>, Vec<(BlockPos, Arc<BlockController>)>) {
+        let (generated, controllers) = game_data.generator().gen_chunk(pos);
+        let registry = game_data.block_controllers();
+        let controllers = controllers
+            .iter()
+            .filter_map(|c| registry.deserialize(game_data, c).map(|controller| (c.pos, controller)))
+            .collect();
+        (PaletteArray::from_fn(|p| BlockState::new(generated[p].load())), controllers)
+    }
 }
 
 #[derive(Eq, PartialEq)]
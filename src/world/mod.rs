@@ -2,54 +2,85 @@ mod chunk_map;
 mod chunk_loading;
 mod inserter;
 mod tick_executor;
+mod fluid;
+mod gravity;
+mod block_update;
+mod scheduled_tick;
+mod random_tick;
+mod entity;
 
 pub mod random;
 pub mod biome;
 pub mod generator;
 pub mod timekeeper;
 pub mod block_controller;
+pub mod save;
 
 pub use self::random::{WorldRngSeeder, WorldGenRng};
-pub use self::chunk_map::{ChunkPos, Chunk, CHUNK_SIZE, BlockPos, chunk_at, ChunkArray};
+pub use self::chunk_map::{ChunkPos, Chunk, CHUNK_SIZE, BlockPos, chunk_at, ChunkArray, PaletteArray};
 pub use self::chunk_loading::LoadGuard;
-pub use self::block_controller::{CreateError, BlockController};
+pub use self::block_controller::{CreateError, BlockController, BlockControllerRegistry, SavedBlockController};
 pub use self::tick_executor::{TickFunction, TickFunctionResult};
+pub use self::scheduled_tick::PendingTick;
+pub use self::fluid::FluidBehavior;
+pub use self::entity::{Entity, EntityId, EntityKind, EntityDraw, EntityView, EntityRegistry, SavedEntity};
 
 use block::AtomicBlockId;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use self::chunk_loading::LoadMap;
 use self::timekeeper::Timekeeper;
 use module::GameData;
 use graphics::ChunkUpdateSender;
-use block::BlockId;
+use block::{BlockId, BlockState};
 use geometry::Direction;
 use self::chunk_map::{ChunkMap};
 use self::inserter::Inserter;
 use self::block_controller::BlockControllerMap;
 use self::tick_executor::TickExecutor;
+use self::gravity::GravityUpdates;
+use self::block_update::BlockUpdates;
+use self::scheduled_tick::ScheduledTicks;
+use self::random_tick::RandomTicks;
+use self::entity::EntityMap;
+use self::save::WorldSave;
+use logging::root_logger;
 
 pub type TimeGuard<'a> = &'a Timekeeper;
 
 pub struct World {
     chunks: ChunkMap,
     block_controllers: BlockControllerMap,
+    entities: EntityMap,
     inserter: Inserter,
     loaded: LoadMap,
     game_data: GameData,
     time: Timekeeper,
     tick_executor: TickExecutor,
+    scheduled_ticks: ScheduledTicks,
+    random_ticks: RandomTicks,
+    gravity: GravityUpdates,
+    block_updates: BlockUpdates,
+    save: Arc<WorldSave>,
 }
 
 impl World {
-    pub fn new(game_data: GameData, chunk_sender: ChunkUpdateSender) -> Self {
+    pub fn new(game_data: GameData, chunk_sender: ChunkUpdateSender, save: WorldSave) -> Self {
+        let save = Arc::new(save);
+        let random_ticks = RandomTicks::new(game_data.seed());
         World {
             chunks: ChunkMap::new(Arc::clone(&game_data), chunk_sender),
             block_controllers: BlockControllerMap::new(),
-            inserter: Inserter::new(Arc::clone(&game_data)),
+            entities: EntityMap::new(),
+            inserter: Inserter::new(Arc::clone(&game_data), Arc::clone(&save)),
             loaded: LoadMap::new(),
             game_data,
             time: Timekeeper::new(),
             tick_executor: TickExecutor::new(),
+            scheduled_ticks: ScheduledTicks::new(),
+            random_ticks,
+            gravity: GravityUpdates::new(),
+            block_updates: BlockUpdates::new(),
+            save,
         }
     }
 
@@ -65,12 +96,70 @@ impl World {
         self.loaded.load_cube(center, radius)
     }
 
+    /// sets the block in its default state
     pub fn set_block(&self, pos: BlockPos, block: BlockId) -> Result<(), ()> {
-        self.chunks.set_block(pos, block)
+        self.set_block_state(pos, BlockState::new(block))
     }
 
     pub fn get_block(&self, pos: BlockPos) -> Option<BlockId> {
-        self.chunks.get_block(pos)
+        self.chunks.get_block_state(pos).map(|s| s.id)
+    }
+
+    /// the block controller at pos is removed if the block is replaced by a different block
+    pub fn set_block_state(&self, pos: BlockPos, state: BlockState) -> Result<(), ()> {
+        let before = self.chunks.set_block_state(pos, state)?;
+        if before.id != state.id {
+            if let Some(controller) = self.block_controllers.kill_block_controller(pos) {
+                controller.on_block_replaced(self, pos);
+            }
+            if let Some(behavior) = self.game_data.blocks().behavior(state.id) {
+                behavior.on_placed(self, pos, state);
+            }
+        }
+        self.block_updates.block_changed(pos);
+        Ok(())
+    }
+
+    pub fn get_block_state(&self, pos: BlockPos) -> Option<BlockState> {
+        self.chunks.get_block_state(pos)
+    }
+
+    /// tick the block at pos after delay ticks, see BlockBehavior::on_scheduled_tick
+    /// fails if the chunk is not loaded
+    pub fn schedule_tick(&self, pos: BlockPos, delay: u64) -> Result<(), ()> {
+        let block = self.get_block(pos).ok_or(())?;
+        self.scheduled_ticks.schedule(pos, block, self.time.current_tick().plus(delay));
+        Ok(())
+    }
+
+    /// attach a controller to the block at pos, BlockController::on_insert is called
+    pub fn create_block_controller(&self, pos: BlockPos, controller: Arc<BlockController>) -> Result<(), CreateError> {
+        self.block_controllers.create_block_controller(pos, controller)
+    }
+
+    /// detach the controller at pos, BlockController::on_remove is called
+    #[allow(dead_code)]
+    pub fn kill_block_controller(&self, pos: BlockPos) -> Option<Arc<BlockController>> {
+        self.block_controllers.kill_block_controller(pos)
+    }
+
+    pub fn get_block_controller(&self, pos: BlockPos) -> Option<Weak<BlockController>> {
+        self.block_controllers.get_block_controller(pos)
+    }
+
+    /// fails if the chunk containing the feet of the entity is not loaded
+    pub fn spawn_entity(&self, entity: Entity) -> Result<EntityId, Entity> {
+        self.entities.spawn(entity)
+    }
+
+    /// removes the entities within radius of position for which filter returns true
+    pub fn take_entities_near<F: FnMut(&Entity) -> bool>(&self, position: [f64; 3], radius: f64, filter: F) -> Vec<Entity> {
+        self.entities.take_near(position, radius, filter)
+    }
+
+    /// the entities as of the end of the last tick
+    pub fn entity_views(&self) -> Vec<EntityView> {
+        self.entities.views()
     }
 
     pub fn natural_light(&self, pos: BlockPos) -> Option<(u8, Option<Direction>)> {
@@ -85,9 +174,42 @@ impl World {
         self.loaded.apply_to_world(&self);
     }
 
+    /// write all currently loaded chunks to disk
+    pub fn save_loaded_chunks(&self) {
+        for pos in self.chunks.loaded_chunks() {
+            if let Some(chunk) = self.chunks.get_chunk(pos) {
+                let ticks = self.scheduled_ticks.chunk_ticks(pos, self.time.current_tick());
+                let controllers = self.block_controllers.chunk_controllers(pos);
+                let entities = self.entities.chunk_entities(pos);
+                let falling = self.gravity.chunk_waiting(pos);
+                self.save_chunk(pos, &chunk, &ticks, &controllers, &entities, &falling);
+            }
+        }
+    }
+
+    fn save_chunk(
+        &self,
+        pos: ChunkPos,
+        chunk: &Chunk,
+        ticks: &[PendingTick],
+        controllers: &[SavedBlockController],
+        entities: &[SavedEntity],
+        falling: &[BlockPos],
+    ) {
+        if let Err(e) = self.save.store_chunk(pos, chunk, ticks, controllers, entities, falling) {
+            error!(root_logger(), "cannot save chunk {:?}: {}", pos, e);
+        }
+    }
+
     pub fn run_tick(&self) {
         let now = self.time().current_tick();
         self.tick_executor.run(&self, now);
+        self.block_updates.run(&self);
+        self.scheduled_ticks.run(&self, now);
+        self.random_ticks.run(&self);
+        self.block_controllers.tick(&self);
+        self.entities.run(&self);
+        self.gravity.run(&self);
     }
 
     pub fn on_tick(&self, f: TickFunction) {
@@ -0,0 +1,45 @@
+use std::sync::Mutex;
+use rand::Rng;
+use world::{World, WorldRngSeeder, WorldGenRng, BlockPos, CHUNK_SIZE};
+
+/// blocks picked per loaded chunk and tick
+const RANDOM_TICKS_PER_CHUNK: usize = 3;
+
+/// ticks random blocks of all loaded chunks, see BlockBehavior::on_random_tick
+pub struct RandomTicks {
+    rng: Mutex<WorldGenRng>,
+}
+
+impl RandomTicks {
+    pub fn new(seed: u64) -> Self {
+        RandomTicks { rng: Mutex::new(WorldRngSeeder::new(seed).push_num(20).rng()) }
+    }
+
+    pub fn run(&self, world: &World) {
+        let blocks = world.game_data().blocks();
+        let cs = CHUNK_SIZE as i32;
+        let mut rng = self.rng.lock().unwrap();
+        for chunk_pos in world.chunks.loaded_chunks() {
+            let chunk = match world.chunks.get_chunk(chunk_pos) {
+                Some(chunk) => chunk,
+                None => continue,
+            };
+            for _ in 0..RANDOM_TICKS_PER_CHUNK {
+                let index = [
+                    rng.gen_range(0, CHUNK_SIZE),
+                    rng.gen_range(0, CHUNK_SIZE),
+                    rng.gen_range(0, CHUNK_SIZE),
+                ];
+                let state = chunk.block_state(index);
+                if let Some(behavior) = blocks.behavior(state.id) {
+                    let pos = BlockPos([
+                        chunk_pos[0] * cs + index[0] as i32,
+                        chunk_pos[1] * cs + index[1] as i32,
+                        chunk_pos[2] * cs + index[2] as i32,
+                    ]);
+                    behavior.on_random_tick(world, pos, state, &mut *rng);
+                }
+            }
+        }
+    }
+}
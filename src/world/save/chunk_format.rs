@@ -0,0 +1,283 @@
+use std::io;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use block::BlockState;
+use world::{Chunk, ChunkPos, BlockPos, PaletteArray, PendingTick, SavedBlockController, SavedEntity, CHUNK_SIZE};
+use super::palette::BlockPalette;
+
+/// version 1 did not store block states
+const FORMAT_VERSION: u32 = 2;
+
+const SECTION_BLOCKS: u8 = 1;
+const SECTION_SCHEDULED_TICKS: u8 = 2;
+const SECTION_BLOCK_CONTROLLERS: u8 = 3;
+const SECTION_ENTITIES: u8 = 4;
+const SECTION_FALLING_BLOCKS: u8 = 5;
+
+/// chunk data read from a save file
+pub struct SavedChunk {
+    pub blocks: PaletteArray<BlockState>,
+    pub scheduled_ticks: Vec<PendingTick>,
+    pub block_controllers: Vec<SavedBlockController>,
+    pub entities: Vec<SavedEntity>,
+    /// falling blocks waiting for the chunk below them to be loaded
+    pub falling_blocks: Vec<BlockPos>,
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn chunk_indices() -> impl Iterator<Item = [usize; 3]> {
+    (0..CHUNK_SIZE).flat_map(|x| {
+        (0..CHUNK_SIZE).flat_map(move |y| (0..CHUNK_SIZE).map(move |z| [x, y, z]))
+    })
+}
+
+/// a chunk is stored as a version number followed by a list of sections
+/// each section consists of a tag, the length of its content and the content
+/// sections with unknown tags are skipped when reading
+pub fn encode(
+    chunk: &Chunk,
+    ticks: &[PendingTick],
+    controllers: &[SavedBlockController],
+    entities: &[SavedEntity],
+    falling_blocks: &[BlockPos],
+    palette: &BlockPalette,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.write_u32::<LittleEndian>(FORMAT_VERSION).unwrap();
+    write_section(&mut out, SECTION_BLOCKS, &encode_blocks(chunk, palette));
+    if !ticks.is_empty() {
+        write_section(&mut out, SECTION_SCHEDULED_TICKS, &encode_ticks(ticks, palette));
+    }
+    if !controllers.is_empty() {
+        write_section(&mut out, SECTION_BLOCK_CONTROLLERS, &encode_controllers(controllers));
+    }
+    if !entities.is_empty() {
+        write_section(&mut out, SECTION_ENTITIES, &encode_entities(entities));
+    }
+    if !falling_blocks.is_empty() {
+        let mut content = Vec::new();
+        for &pos in falling_blocks {
+            write_pos_in_chunk(&mut content, pos);
+        }
+        write_section(&mut out, SECTION_FALLING_BLOCKS, &content);
+    }
+    out
+}
+
+pub fn decode(mut data: &[u8], pos: ChunkPos, palette: &BlockPalette) -> io::Result<SavedChunk> {
+    let version = data.read_u32::<LittleEndian>()?;
+    if version != FORMAT_VERSION && version != 1 {
+        return Err(invalid_data("unsupported chunk format version"));
+    }
+    let mut saved_blocks = None;
+    let mut scheduled_ticks = Vec::new();
+    let mut block_controllers = Vec::new();
+    let mut entities = Vec::new();
+    let mut falling_blocks = Vec::new();
+    while !data.is_empty() {
+        let tag = data.read_u8()?;
+        let len = data.read_u32::<LittleEndian>()? as usize;
+        if len > data.len() {
+            return Err(invalid_data("chunk section exceeds chunk data"));
+        }
+        let (content, rest) = data.split_at(len);
+        data = rest;
+        if tag == SECTION_BLOCKS {
+            saved_blocks = Some(decode_blocks(content, palette, version > 1)?);
+        } else if tag == SECTION_SCHEDULED_TICKS {
+            scheduled_ticks = decode_ticks(content, pos, palette)?;
+        } else if tag == SECTION_BLOCK_CONTROLLERS {
+            block_controllers = decode_controllers(content, pos)?;
+        } else if tag == SECTION_ENTITIES {
+            entities = decode_entities(content)?;
+        } else if tag == SECTION_FALLING_BLOCKS {
+            let mut content = content;
+            while !content.is_empty() {
+                falling_blocks.push(read_pos_in_chunk(&mut content, pos)?);
+            }
+        }
+    }
+    Ok(SavedChunk {
+        blocks: saved_blocks.ok_or_else(|| invalid_data("missing block section"))?,
+        scheduled_ticks,
+        block_controllers,
+        entities,
+        falling_blocks,
+    })
+}
+
+fn write_section(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.write_u8(tag).unwrap();
+    out.write_u32::<LittleEndian>(content.len() as u32).unwrap();
+    out.extend_from_slice(content);
+}
+
+/// blocks are run length encoded as (run length, saved block id, meta) triples
+fn encode_blocks(chunk: &Chunk, palette: &BlockPalette) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut run: Option<(u32, (u32, u32))> = None;
+    let blocks = chunk.blocks();
+    let write_run = |out: &mut Vec<u8>, len, (id, meta)| {
+        out.write_u32::<LittleEndian>(len).unwrap();
+        out.write_u32::<LittleEndian>(id).unwrap();
+        out.write_u32::<LittleEndian>(meta).unwrap();
+    };
+    for index in chunk_indices() {
+        let state = blocks.get(index);
+        let saved = palette.state_to_saved(state);
+        run = match run {
+            Some((len, run_state)) if run_state == saved => Some((len + 1, saved)),
+            Some((len, run_state)) => {
+                write_run(&mut out, len, run_state);
+                Some((1, saved))
+            }
+            None => Some((1, saved)),
+        };
+    }
+    if let Some((len, run_state)) = run {
+        write_run(&mut out, len, run_state);
+    }
+    out
+}
+
+fn decode_blocks(mut data: &[u8], palette: &BlockPalette, with_meta: bool) -> io::Result<PaletteArray<BlockState>> {
+    let mut array = PaletteArray::new(BlockState::default());
+    let mut indices = chunk_indices();
+    while !data.is_empty() {
+        let len = data.read_u32::<LittleEndian>()?;
+        let saved = data.read_u32::<LittleEndian>()?;
+        let meta = if with_meta { data.read_u32::<LittleEndian>()? } else { 0 };
+        let state = palette
+            .state_from_saved(saved, meta)
+            .ok_or_else(|| invalid_data("block id not in palette"))?;
+        for _ in 0..len {
+            let index = indices.next().ok_or_else(|| invalid_data("too many blocks"))?;
+            array.set(index, state);
+        }
+    }
+    if indices.next().is_some() {
+        return Err(invalid_data("too few blocks"));
+    }
+    Ok(array)
+}
+
+/// each tick is stored as its position in the chunk, saved block id and remaining delay
+fn encode_ticks(ticks: &[PendingTick], palette: &BlockPalette) -> Vec<u8> {
+    let mut out = Vec::new();
+    for tick in ticks {
+        write_pos_in_chunk(&mut out, tick.pos);
+        out.write_u32::<LittleEndian>(palette.to_saved(tick.block)).unwrap();
+        out.write_u64::<LittleEndian>(tick.delay).unwrap();
+    }
+    out
+}
+
+fn decode_ticks(mut data: &[u8], pos: ChunkPos, palette: &BlockPalette) -> io::Result<Vec<PendingTick>> {
+    let mut ticks = Vec::new();
+    while !data.is_empty() {
+        let tick_pos = read_pos_in_chunk(&mut data, pos)?;
+        let block = palette
+            .from_saved(data.read_u32::<LittleEndian>()?)
+            .ok_or_else(|| invalid_data("block id not in palette"))?;
+        let delay = data.read_u64::<LittleEndian>()?;
+        ticks.push(PendingTick { pos: tick_pos, block, delay });
+    }
+    Ok(ticks)
+}
+
+fn write_pos_in_chunk(out: &mut Vec<u8>, pos: BlockPos) {
+    for &i in &pos.pos_in_chunk().1 {
+        out.write_u8(i as u8).unwrap();
+    }
+}
+
+fn read_pos_in_chunk(data: &mut &[u8], chunk: ChunkPos) -> io::Result<BlockPos> {
+    let cs = CHUNK_SIZE as i32;
+    let mut pos = BlockPos([chunk[0] * cs, chunk[1] * cs, chunk[2] * cs]);
+    for i in 0..3 {
+        let offset = data.read_u8()?;
+        if offset as usize >= CHUNK_SIZE {
+            return Err(invalid_data("position outside of chunk"));
+        }
+        pos.0[i] += i32::from(offset);
+    }
+    Ok(pos)
+}
+
+/// each controller is stored as its position in the chunk, type name and data, both prefixed by their length
+fn encode_controllers(controllers: &[SavedBlockController]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for controller in controllers {
+        write_pos_in_chunk(&mut out, controller.pos);
+        out.write_u32::<LittleEndian>(controller.type_name.len() as u32).unwrap();
+        out.extend_from_slice(controller.type_name.as_bytes());
+        out.write_u32::<LittleEndian>(controller.data.len() as u32).unwrap();
+        out.extend_from_slice(&controller.data);
+    }
+    out
+}
+
+fn decode_controllers(mut data: &[u8], pos: ChunkPos) -> io::Result<Vec<SavedBlockController>> {
+    let mut controllers = Vec::new();
+    while !data.is_empty() {
+        let controller_pos = read_pos_in_chunk(&mut data, pos)?;
+        let type_name = String::from_utf8(read_bytes(&mut data)?.to_vec())
+            .map_err(|_| invalid_data("block controller name is not utf-8"))?;
+        let controller_data = read_bytes(&mut data)?.to_vec();
+        controllers.push(SavedBlockController { pos: controller_pos, type_name, data: controller_data });
+    }
+    Ok(controllers)
+}
+
+/// each entity is stored as its type name, position, velocity and data
+/// name and data are prefixed by their length
+fn encode_entities(entities: &[SavedEntity]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for entity in entities {
+        out.write_u32::<LittleEndian>(entity.type_name.len() as u32).unwrap();
+        out.extend_from_slice(entity.type_name.as_bytes());
+        for &x in entity.position.iter().chain(entity.velocity.iter()) {
+            out.write_f64::<LittleEndian>(x).unwrap();
+        }
+        out.write_u32::<LittleEndian>(entity.data.len() as u32).unwrap();
+        out.extend_from_slice(&entity.data);
+    }
+    out
+}
+
+fn decode_entities(mut data: &[u8]) -> io::Result<Vec<SavedEntity>> {
+    let mut entities = Vec::new();
+    while !data.is_empty() {
+        let type_name = String::from_utf8(read_bytes(&mut data)?.to_vec())
+            .map_err(|_| invalid_data("entity name is not utf-8"))?;
+        let mut vectors = [[0.; 3]; 2];
+        for v in &mut vectors {
+            for x in v.iter_mut() {
+                *x = data.read_f64::<LittleEndian>()?;
+            }
+        }
+        if !vectors.iter().all(|v| v.iter().all(|x| x.is_finite())) {
+            return Err(invalid_data("entity position is not finite"));
+        }
+        let entity_data = read_bytes(&mut data)?.to_vec();
+        entities.push(SavedEntity {
+            type_name,
+            position: vectors[0],
+            velocity: vectors[1],
+            data: entity_data,
+        });
+    }
+    Ok(entities)
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    let len = data.read_u32::<LittleEndian>()? as usize;
+    if len > data.len() {
+        return Err(invalid_data("length exceeds chunk section"));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
@@ -0,0 +1,59 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use rand;
+use logging::root_logger;
+
+/// world properties which do not change after creation
+/// stored as key value pairs, one per line
+pub struct WorldMeta {
+    pub seed: u64,
+}
+
+const META_FILE: &str = "world.meta";
+
+impl WorldMeta {
+    /// reads the metadata of the world at path
+    /// if the world does not exist it is created using the given seed or a random one
+    pub fn load_or_create(path: &Path, seed: Option<u64>) -> io::Result<Self> {
+        let file = path.join(META_FILE);
+        if file.exists() {
+            let meta = Self::read(&file)?;
+            if seed.map_or(false, |s| s != meta.seed) {
+                warn!(root_logger(), "world {:?} already exists, ignoring seed argument", path);
+            }
+            Ok(meta)
+        } else {
+            let meta = WorldMeta {
+                seed: seed.unwrap_or_else(rand::random),
+            };
+            fs::create_dir_all(path)?;
+            meta.write(&file)?;
+            Ok(meta)
+        }
+    }
+
+    fn read(file: &Path) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut seed = None;
+        for line in BufReader::new(File::open(file)?).lines() {
+            let line = line?;
+            let mut kv = line.splitn(2, '=');
+            match (kv.next().map(str::trim), kv.next().map(str::trim)) {
+                (Some("seed"), Some(value)) => {
+                    seed = Some(value.parse().map_err(|_| invalid(format!("invalid seed: {}", value)))?);
+                }
+                (Some(""), None) => {}
+                _ => return Err(invalid(format!("invalid line in world metadata: {}", line))),
+            }
+        }
+        Ok(WorldMeta {
+            seed: seed.ok_or_else(|| invalid("world metadata contains no seed".into()))?,
+        })
+    }
+
+    fn write(&self, file: &Path) -> io::Result<()> {
+        let mut file = File::create(file)?;
+        writeln!(file, "seed={}", self.seed)
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use num::Integer;
+use block::BlockRegistry;
+use world::{Chunk, ChunkPos, BlockPos, PendingTick, SavedBlockController, SavedEntity};
+use logging::root_logger;
+
+mod region;
+mod chunk_format;
+mod palette;
+mod meta;
+
+pub use self::chunk_format::SavedChunk;
+pub use self::palette::MISSING_BLOCK;
+pub use self::meta::WorldMeta;
+
+use self::palette::BlockPalette;
+
+use self::region::{RegionFile, REGION_SIZE};
+
+/// on disk storage of a world
+/// chunks are grouped into region files of REGION_SIZE^3 chunks
+/// block ids are translated using a palette stored with the world
+/// chunks which cannot be read are copied to the corrupt directory before they can be overwritten
+pub struct WorldSave {
+    path: PathBuf,
+    regions: Mutex<HashMap<[i32; 3], RegionFile>>,
+    palette: BlockPalette,
+    /// unreadable chunks which could not be copied, these are never overwritten
+    protected: Mutex<HashSet<ChunkPos>>,
+}
+
+impl WorldSave {
+    pub fn open<P: Into<PathBuf>>(path: P, blocks: &BlockRegistry) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(path.join("regions"))?;
+        Ok(WorldSave {
+            palette: BlockPalette::open(&path.join("blocks.palette"), blocks)?,
+            regions: Mutex::new(HashMap::new()),
+            protected: Mutex::new(HashSet::new()),
+            path,
+        })
+    }
+
+    /// returns None if the chunk has never been stored
+    pub fn load_chunk(&self, pos: ChunkPos) -> io::Result<Option<SavedChunk>> {
+        let (region, index) = Self::region_index(pos);
+        let data = match self.with_region(region, false, |r| r.read(index)) {
+            Ok(Some(Some(data))) => data,
+            Ok(Some(None)) | Ok(None) => return Ok(None),
+            Err(e) => {
+                self.protected.lock().unwrap().insert(pos);
+                return Err(e);
+            }
+        };
+        chunk_format::decode(&data, pos, &self.palette).map(Some).map_err(|e| {
+            self.keep_corrupt(pos, &data);
+            e
+        })
+    }
+
+    /// copies the data of a chunk which cannot be decoded, so regenerating the chunk does not destroy it
+    fn keep_corrupt(&self, pos: ChunkPos, data: &[u8]) {
+        let dir = self.path.join("corrupt");
+        let result = fs::create_dir_all(&dir).and_then(|_| {
+            let name = format!("{}_{}_{}", pos[0], pos[1], pos[2]);
+            let mut i = 0;
+            loop {
+                let path = dir.join(format!("{}.{}.chunk", name, i));
+                match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                    Ok(mut file) => return file.write_all(data).and_then(|_| file.sync_all()).map(|_| path),
+                    Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => i += 1,
+                    Err(e) => return Err(e),
+                }
+            }
+        });
+        match result {
+            Ok(path) => warn!(root_logger(), "corrupt chunk {:?} copied to {}", pos, path.display()),
+            Err(e) => {
+                error!(root_logger(), "cannot copy corrupt chunk {:?}, it will not be overwritten: {}", pos, e);
+                self.protected.lock().unwrap().insert(pos);
+            }
+        }
+    }
+
+    pub fn store_chunk(
+        &self,
+        pos: ChunkPos,
+        chunk: &Chunk,
+        ticks: &[PendingTick],
+        controllers: &[SavedBlockController],
+        entities: &[SavedEntity],
+        falling_blocks: &[BlockPos],
+    ) -> io::Result<()> {
+        if self.protected.lock().unwrap().contains(&pos) {
+            return Err(io::Error::new(io::ErrorKind::Other, "stored chunk is unreadable and has not been copied"));
+        }
+        let data = chunk_format::encode(chunk, ticks, controllers, entities, falling_blocks, &self.palette);
+        let (region, index) = Self::region_index(pos);
+        self.with_region(region, true, |r| r.write(index, &data))
+            .map(|_| ())
+    }
+
+    /// if create is false and the region file does not exist, f is not called
+    fn with_region<R, F>(&self, region: [i32; 3], create: bool, f: F) -> io::Result<Option<R>>
+        where F: FnOnce(&mut RegionFile) -> io::Result<R>
+    {
+        let mut regions = self.regions.lock().unwrap();
+        if !regions.contains_key(&region) {
+            let path = self.region_path(region);
+            if !create && !path.exists() {
+                return Ok(None);
+            }
+            regions.insert(region, RegionFile::open(&path)?);
+        }
+        f(regions.get_mut(&region).unwrap()).map(Some)
+    }
+
+    fn region_path(&self, region: [i32; 3]) -> PathBuf {
+        self.path
+            .join("regions")
+            .join(format!("{}_{}_{}.region", region[0], region[1], region[2]))
+    }
+
+    fn region_index(pos: ChunkPos) -> ([i32; 3], usize) {
+        let rs = REGION_SIZE as i32;
+        let (x, rx) = pos[0].div_mod_floor(&rs);
+        let (y, ry) = pos[1].div_mod_floor(&rs);
+        let (z, rz) = pos[2].div_mod_floor(&rs);
+        ([x, y, z], ((rx * rs + ry) * rs + rz) as usize)
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use block::{BlockId, BlockState, BlockRegistry};
+use logging::root_logger;
+
+/// name of the block used for saved blocks which are no longer registered
+pub const MISSING_BLOCK: &str = "missing";
+
+/// maps block ids to the ids used in the save files of a world
+/// the palette is stored as a list of block names, the saved id of a block is its line number
+/// names are never removed from the palette, so blocks keep their saved id
+/// even if they are missing for some time
+/// saved blocks which are not registered are loaded as MISSING_BLOCK,
+/// the meta of the placeholder refers to the saved id and meta, so they are written back unchanged
+pub struct BlockPalette {
+    to_saved: Vec<u32>,
+    from_saved: Vec<BlockId>,
+    missing: BlockId,
+    unknown: HashSet<u32>,
+    missing_states: Mutex<MissingStates>,
+}
+
+/// saved (id, meta) pairs of unknown blocks, indexed by the meta of the placeholder
+#[derive(Default)]
+struct MissingStates {
+    states: Vec<(u32, u32)>,
+    indices: HashMap<(u32, u32), u32>,
+}
+
+impl BlockPalette {
+    /// reads the palette file at path and appends all blocks not yet contained to it
+    pub fn open(path: &Path, blocks: &BlockRegistry) -> io::Result<Self> {
+        let mut names = Vec::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                names.push(line?);
+            }
+        }
+        let missing = blocks
+            .by_name(MISSING_BLOCK)
+            .expect("placeholder block for missing blocks not registered");
+        let mut from_saved = Vec::with_capacity(names.len());
+        let mut saved_ids = HashMap::new();
+        let mut unknown = HashSet::new();
+        for (saved, name) in names.iter().enumerate() {
+            saved_ids.insert(name.clone(), saved as u32);
+            from_saved.push(match blocks.by_name(name) {
+                Some(id) => id,
+                None => {
+                    error!(root_logger(), "saved block {:?} does not exist, replacing with {:?}", name, MISSING_BLOCK);
+                    unknown.insert(saved as u32);
+                    missing
+                }
+            });
+        }
+        let mut to_saved = Vec::new();
+        for id in blocks.ids() {
+            let name = blocks.name(id);
+            let saved = match saved_ids.get(name) {
+                Some(&saved) => saved,
+                None => {
+                    names.push(name.into());
+                    from_saved.push(id);
+                    names.len() as u32 - 1
+                }
+            };
+            to_saved.push(saved);
+        }
+        //the palette is replaced at once, so a failed write does not leave a truncated palette
+        let mut temp_name = path.as_os_str().to_owned();
+        temp_name.push(".tmp");
+        let temp_path = Path::new(&temp_name);
+        {
+            let mut file = File::create(temp_path)?;
+            for name in &names {
+                writeln!(file, "{}", name)?;
+            }
+            file.sync_data()?;
+        }
+        fs::rename(temp_path, path)?;
+        Ok(BlockPalette {
+            to_saved,
+            from_saved,
+            missing,
+            unknown,
+            missing_states: Mutex::new(MissingStates::default()),
+        })
+    }
+
+    /// the state a saved (id, meta) pair is loaded as
+    pub fn state_from_saved(&self, saved: u32, meta: u32) -> Option<BlockState> {
+        let id = self.from_saved(saved)?;
+        if !self.unknown.contains(&saved) {
+            return Some(BlockState::with_meta(id, meta));
+        }
+        let mut missing = self.missing_states.lock().unwrap();
+        let next = missing.states.len() as u32;
+        let index = *missing.indices.entry((saved, meta)).or_insert(next);
+        if index == next {
+            missing.states.push((saved, meta));
+        }
+        Some(BlockState::with_meta(self.missing, index))
+    }
+
+    /// the (id, meta) pair a state is saved as
+    pub fn state_to_saved(&self, state: BlockState) -> (u32, u32) {
+        if state.id == self.missing {
+            if let Some(&saved) = self.missing_states.lock().unwrap().states.get(state.meta as usize) {
+                return saved;
+            }
+        }
+        (self.to_saved(state.id), state.meta)
+    }
+
+    pub fn to_saved(&self, id: BlockId) -> u32 {
+        self.to_saved[id.to_u32() as usize]
+    }
+
+    pub fn from_saved(&self, saved: u32) -> Option<BlockId> {
+        self.from_saved.get(saved as usize).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    use std::process;
+    use block::{Block, LightType};
+    use graphics::DrawType;
+
+    #[test]
+    fn unknown_blocks_are_written_back() {
+        let path = temp_dir().join(format!("block_game_palette_test_{}.palette", process::id()));
+        {
+            let mut file = File::create(&path).unwrap();
+            writeln!(file, "{}\nstone\ngone", MISSING_BLOCK).unwrap();
+        }
+        let mut blocks = BlockRegistry::new();
+        blocks.add(Block::new(DrawType::None, LightType::Transparent, MISSING_BLOCK.into()));
+        let stone = blocks.add(Block::new(DrawType::None, LightType::Opaque, "stone".into()));
+        let palette = BlockPalette::open(&path, &blocks);
+        remove_file(&path).unwrap();
+        let palette = palette.unwrap();
+        let loaded = palette.state_from_saved(2, 5).unwrap();
+        assert_eq!(blocks.name(loaded.id), MISSING_BLOCK);
+        assert_eq!(palette.state_to_saved(loaded), (2, 5));
+        assert_eq!(palette.state_from_saved(2, 5), Some(loaded));
+        let stone_state = palette.state_from_saved(1, 3).unwrap();
+        assert_eq!(stone_state, BlockState::with_meta(stone, 3));
+        assert_eq!(palette.state_to_saved(stone_state), (1, 3));
+    }
+}
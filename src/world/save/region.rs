@@ -0,0 +1,153 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+/// number of chunks along each axis of a region
+pub const REGION_SIZE: usize = 16;
+pub const CHUNKS_PER_REGION: usize = REGION_SIZE * REGION_SIZE * REGION_SIZE;
+const HEADER_ENTRY_SIZE: u64 = 8;
+const HEADER_SIZE: u64 = CHUNKS_PER_REGION as u64 * HEADER_ENTRY_SIZE;
+
+/// a file containing the data of REGION_SIZE^3 chunks
+/// the file starts with a table of (offset, length) pairs, one for each chunk
+/// chunks which have never been stored have length 0
+pub struct RegionFile {
+    file: File,
+    table: Vec<(u32, u32)>,
+}
+
+impl RegionFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        if file.metadata()?.len() < HEADER_SIZE {
+            file.set_len(HEADER_SIZE)?;
+        }
+        let mut header = vec![0; HEADER_SIZE as usize];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut header)?;
+        let mut reader = &header[..];
+        let mut table = Vec::with_capacity(CHUNKS_PER_REGION);
+        for _ in 0..CHUNKS_PER_REGION {
+            let offset = reader.read_u32::<LittleEndian>()?;
+            let len = reader.read_u32::<LittleEndian>()?;
+            table.push((offset, len));
+        }
+        Ok(RegionFile { file, table })
+    }
+
+    pub fn read(&mut self, index: usize) -> io::Result<Option<Vec<u8>>> {
+        let (offset, len) = self.table[index];
+        if len == 0 {
+            return Ok(None);
+        }
+        let mut data = vec![0; len as usize];
+        self.file.seek(SeekFrom::Start(u64::from(offset)))?;
+        self.file.read_exact(&mut data)?;
+        Ok(Some(data))
+    }
+
+    /// data is written to space not used by any chunk, including the old data of this chunk
+    /// the table entry is only updated after the data has been written, so a crash leaves either the old or the new data
+    pub fn write(&mut self, index: usize, data: &[u8]) -> io::Result<()> {
+        assert!(!data.is_empty(), "cannot store empty chunk data");
+        let offset = match self.find_free(data.len() as u64) {
+            Some(offset) => self.file.seek(SeekFrom::Start(offset))?,
+            None => self.file.seek(SeekFrom::End(0))?,
+        };
+        if offset + data.len() as u64 > u64::from(u32::max_value()) {
+            return Err(io::Error::new(io::ErrorKind::Other, "region file too large"));
+        }
+        self.file.write_all(data)?;
+        self.file.sync_data()?;
+        let entry = (offset as u32, data.len() as u32);
+        self.file.seek(SeekFrom::Start(index as u64 * HEADER_ENTRY_SIZE))?;
+        self.file.write_u32::<LittleEndian>(entry.0)?;
+        self.file.write_u32::<LittleEndian>(entry.1)?;
+        self.table[index] = entry;
+        Ok(())
+    }
+
+    /// the first gap of at least len bytes between stored chunks
+    fn find_free(&self, len: u64) -> Option<u64> {
+        let mut used: Vec<(u64, u64)> = self.table
+            .iter()
+            .filter(|e| e.1 > 0)
+            .map(|&(offset, len)| (u64::from(offset), u64::from(offset) + u64::from(len)))
+            .collect();
+        used.sort_unstable();
+        let mut free_start = HEADER_SIZE;
+        for (start, end) in used {
+            if start >= free_start + len {
+                return Some(free_start);
+            }
+            free_start = free_start.max(end);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// removes the file when the test ends, even if it fails
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = remove_file(&self.0);
+        }
+    }
+
+    fn temp_region() -> TempFile {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        TempFile(temp_dir().join(format!(
+            "block_game_region_test_{}_{}.region",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        )))
+    }
+
+    #[test]
+    fn write_read() {
+        let file = temp_region();
+        {
+            let mut region = RegionFile::open(&file.0).unwrap();
+            assert_eq!(region.read(5).unwrap(), None);
+            region.write(5, &[1, 2, 3]).unwrap();
+            region.write(7, &[4, 5]).unwrap();
+            region.write(5, &[6, 7, 8, 9]).unwrap();
+            region.write(7, &[10]).unwrap();
+        }
+        let mut region = RegionFile::open(&file.0).unwrap();
+        assert_eq!(region.read(5).unwrap(), Some(vec![6, 7, 8, 9]));
+        assert_eq!(region.read(7).unwrap(), Some(vec![10]));
+        assert_eq!(region.read(6).unwrap(), None);
+    }
+
+    #[test]
+    fn keeps_old_data_until_replaced() {
+        let file = temp_region();
+        let mut region = RegionFile::open(&file.0).unwrap();
+        region.write(1, &[1, 2, 3]).unwrap();
+        let old = region.table[1];
+        region.write(1, &[4]).unwrap();
+        let new = region.table[1];
+        assert!(new.0 + new.1 <= old.0 || new.0 >= old.0 + old.1);
+        //the space of the old data is reused once it is no longer referenced
+        region.write(2, &[5, 6]).unwrap();
+        assert_eq!(region.table[2].0, old.0);
+        assert_eq!(region.read(1).unwrap(), Some(vec![4]));
+        assert_eq!(region.read(2).unwrap(), Some(vec![5, 6]));
+    }
+}
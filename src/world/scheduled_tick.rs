@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::sync::Mutex;
+use block::BlockId;
+use world::{World, BlockPos, ChunkPos};
+use world::timekeeper::TickId;
+
+/// ticks exceeding this are postponed to the next tick
+const MAX_TICKS_PER_TICK: usize = 4096;
+
+/// a block tick scheduled for a block in a chunk which is not loaded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PendingTick {
+    pub pos: BlockPos,
+    pub block: BlockId,
+    /// remaining ticks until the tick fires
+    pub delay: u64,
+}
+
+/// block ticks scheduled to fire after some delay
+/// a tick only fires if the chunk is still loaded and the block has not been replaced by a different block
+/// each block at a position has at most one scheduled tick, the earliest one
+#[derive(Default)]
+pub struct ScheduledTicks {
+    queue: Mutex<Queue>,
+}
+
+#[derive(Default)]
+struct Queue {
+    //may contain ticks which have been superseded, positions is authoritative
+    by_tick: BTreeMap<TickId, Vec<(BlockPos, BlockId)>>,
+    positions: HashMap<(BlockPos, BlockId), TickId>,
+}
+
+impl Queue {
+    fn insert(&mut self, pos: BlockPos, tick: TickId, block: BlockId) {
+        if let Some(&scheduled) = self.positions.get(&(pos, block)) {
+            if scheduled <= tick {
+                return;
+            }
+        }
+        self.positions.insert((pos, block), tick);
+        self.by_tick.entry(tick).or_insert_with(Vec::new).push((pos, block));
+    }
+
+    /// removes the ticks due at now and returns at most MAX_TICKS_PER_TICK of them, the rest is postponed
+    fn take_due(&mut self, now: TickId) -> Vec<(BlockPos, BlockId)> {
+        let later = self.by_tick.split_off(&now.next());
+        let mut due = Vec::new();
+        for (tick, keys) in mem::replace(&mut self.by_tick, later) {
+            for key in keys {
+                if self.positions.get(&key) == Some(&tick) {
+                    due.push(key);
+                }
+            }
+        }
+        if due.len() > MAX_TICKS_PER_TICK {
+            let postponed = due.split_off(MAX_TICKS_PER_TICK);
+            for &key in &postponed {
+                self.positions.insert(key, now.next());
+            }
+            self.by_tick.entry(now.next()).or_insert_with(Vec::new).extend(postponed);
+        }
+        for key in &due {
+            self.positions.remove(key);
+        }
+        due
+    }
+}
+
+impl ScheduledTicks {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn schedule(&self, pos: BlockPos, block: BlockId, tick: TickId) {
+        self.queue.lock().unwrap().insert(pos, tick, block);
+    }
+
+    /// removes the ticks of a chunk which is unloaded
+    pub fn unload_chunk(&self, chunk: ChunkPos, now: TickId) -> Vec<PendingTick> {
+        let mut queue = self.queue.lock().unwrap();
+        let ticks = Self::pending(&queue, chunk, now);
+        for tick in &ticks {
+            queue.positions.remove(&(tick.pos, tick.block));
+        }
+        ticks
+    }
+
+    /// the ticks of a chunk which is saved while staying loaded
+    pub fn chunk_ticks(&self, chunk: ChunkPos, now: TickId) -> Vec<PendingTick> {
+        Self::pending(&self.queue.lock().unwrap(), chunk, now)
+    }
+
+    pub fn load_chunk(&self, ticks: &[PendingTick], now: TickId) {
+        let mut queue = self.queue.lock().unwrap();
+        for tick in ticks {
+            queue.insert(tick.pos, now.plus(tick.delay), tick.block);
+        }
+    }
+
+    fn pending(queue: &Queue, chunk: ChunkPos, now: TickId) -> Vec<PendingTick> {
+        queue
+            .positions
+            .iter()
+            .filter(|&(&(pos, _), _)| pos.pos_in_chunk().0 == chunk)
+            .map(|(&(pos, block), &tick)| {
+                PendingTick {
+                    pos,
+                    block,
+                    delay: if tick > now { tick.ticks_since(now) } else { 0 },
+                }
+            })
+            .collect()
+    }
+
+    pub fn run(&self, world: &World, now: TickId) {
+        let due = self.queue.lock().unwrap().take_due(now);
+        let blocks = world.game_data().blocks();
+        for (pos, block) in due {
+            let state = match world.get_block_state(pos) {
+                Some(state) if state.id == block => state,
+                _ => continue,
+            };
+            if let Some(behavior) = blocks.behavior(block) {
+                behavior.on_scheduled_tick(world, pos, state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use block::{Block, BlockRegistry, LightType};
+    use graphics::DrawType;
+
+    #[test]
+    fn ticks_of_different_blocks_are_kept() {
+        let mut blocks = BlockRegistry::new();
+        let a = blocks.add(Block::new(DrawType::None, LightType::Transparent, "a".into()));
+        let b = blocks.add(Block::new(DrawType::None, LightType::Transparent, "b".into()));
+        let pos = BlockPos([1, 2, 3]);
+        let now = TickId::zero();
+        let mut queue = Queue::default();
+        queue.insert(pos, now.plus(2), a);
+        queue.insert(pos, now.plus(5), a);
+        queue.insert(pos, now.plus(3), b);
+        assert!(queue.take_due(now.plus(1)).is_empty());
+        assert_eq!(queue.take_due(now.plus(2)), vec![(pos, a)]);
+        assert_eq!(queue.take_due(now.plus(3)), vec![(pos, b)]);
+        assert!(queue.take_due(now.plus(10)).is_empty());
+    }
+}
@@ -21,6 +21,9 @@ impl TickId {
     pub fn ticks_since(self, other: Self) -> u64 {
         self.0 - other.0
     }
+    pub fn plus(self, ticks: u64) -> Self {
+        TickId(self.0 + ticks)
+    }
 }
 
 pub struct Timekeeper {